@@ -0,0 +1,65 @@
+"""scenes/volumes/plume_*.vol（煙の柱と根元の火球）を生成する
+
+    python3 scenes/volumes/gen_plume.py
+"""
+import math
+import os
+import struct
+
+NX, NY, NZ = 48, 64, 48
+MIN = (-1.2, 0.0, -1.2)
+MAX = (1.2, 3.2, 1.2)
+
+# 格子点ごとの疑似乱数
+def h(i, j, k):
+    n = (i * 73856093) ^ (j * 19349663) ^ (k * 83492791)
+    n = (n ^ (n >> 13)) * 1274126177
+    return ((n ^ (n >> 16)) & 0xFFFF) / 65535.0
+
+def vnoise(x, y, z):
+    i, j, k = math.floor(x), math.floor(y), math.floor(z)
+    fx, fy, fz = x - i, y - j, z - k
+    sx, sy, sz = [f * f * (3 - 2 * f) for f in (fx, fy, fz)]
+    def lerp(a, b, t): return a + (b - a) * t
+    c = [[[h(i + a, j + b, k + c_) for c_ in (0, 1)] for b in (0, 1)] for a in (0, 1)]
+    x00 = lerp(c[0][0][0], c[1][0][0], sx); x10 = lerp(c[0][1][0], c[1][1][0], sx)
+    x01 = lerp(c[0][0][1], c[1][0][1], sx); x11 = lerp(c[0][1][1], c[1][1][1], sx)
+    return lerp(lerp(x00, x10, sy), lerp(x01, x11, sy), sz)
+
+def fbm(x, y, z):
+    return sum(vnoise(x * 2 ** o, y * 2 ** o, z * 2 ** o) / 2 ** o for o in range(3)) / 1.75
+
+def smoothstep(a, b, x):
+    t = min(max((x - a) / (b - a), 0.0), 1.0)
+    return t * t * (3 - 2 * t)
+
+density, temperature = bytearray(), bytearray()
+for k in range(NZ):
+    for j in range(NY):
+        for i in range(NX):
+            x = MIN[0] + (MAX[0] - MIN[0]) * i / (NX - 1)
+            y = MIN[1] + (MAX[1] - MIN[1]) * j / (NY - 1)
+            z = MIN[2] + (MAX[2] - MIN[2]) * k / (NZ - 1)
+            n = fbm(x * 2.5 + 7.0, y * 2.5 - 1.3 * y, z * 2.5 + 3.0)
+            # 上に広がりながら揺らぐ煙の柱
+            cx = 0.25 * math.sin(y * 1.7)
+            r = math.hypot(x - cx, z)
+            radius = 0.3 + 0.22 * y
+            plume = smoothstep(radius, radius * 0.4, r) * smoothstep(3.1, 2.2, y)
+            # 根元の火球
+            ball = smoothstep(0.65, 0.2, math.hypot(x, y - 0.55, z))
+            d = max(plume, ball) * smoothstep(0.35, 0.75, n + 0.25 * max(plume, ball))
+            t = ball * smoothstep(0.3, 0.8, n) * smoothstep(0.0, 0.4, y)
+            density.append(min(255, int(d * 255 + 0.5)))
+            temperature.append(min(255, int(t * 255 + 0.5)))
+
+# Mitsubaのgrid volume形式（uint8、1チャンネル）で書き出す
+def write(name, data):
+    with open(os.path.join(os.path.dirname(os.path.abspath(__file__)), name), 'wb') as f:
+        f.write(b'VOL' + bytes([3]))
+        f.write(struct.pack('<iiiii', 3, NX, NY, NZ, 1))
+        f.write(struct.pack('<6f', *MIN, *MAX))
+        f.write(data)
+
+write('plume_density.vol', density)
+write('plume_temperature.vol', temperature)
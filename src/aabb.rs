@@ -2,6 +2,7 @@ use crate::ray::Ray;
 use crate::vec3_glam::Vec3Glam;
 
 /// 軸並行境界ボックス（Axis-Aligned Bounding Box）
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy)]
 pub struct AABB {
     minimum: Vec3Glam, // ボックスの最小点
@@ -47,6 +48,35 @@ impl AABB {
         true
     }
 
+    /// レイがAABBの内部を通る区間を計算
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(f64, f64)>` - t_min ~ t_maxに制限した区間のレイのパラメータ（通らない場合はNone）
+    pub fn interval(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        for a in 0..3 {
+            let inv_d = 1.0 / ray.direction().get(a);
+            let mut t0 = (self.minimum.get(a) - ray.origin().get(a)) * inv_d;
+            let mut t1 = (self.maximum.get(a) - ray.origin().get(a)) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // 0 * ∞ によるNaNは区間を制限しない
+            if !t0.is_nan() {
+                t_min = t0.max(t_min);
+            }
+            if !t1.is_nan() {
+                t_max = t1.min(t_max);
+            }
+            if t_max <= t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
     /// 2つのAABBを含む最小のAABBを生成
     pub fn surrounding_box(box0: &AABB, box1: &AABB) -> AABB {
         let small = Vec3Glam::new(
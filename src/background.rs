@@ -0,0 +1,87 @@
+use crate::image::ImageData;
+use crate::vec3_glam::{ColorGlam, Vec3Glam};
+
+/// レイがどのオブジェクトにも当たらなかった場合の放射輝度を定義するトレイト
+pub trait Background: Send + Sync {
+    /// 指定方向の背景の放射輝度を計算
+    fn radiance(&self, direction: Vec3Glam) -> ColorGlam;
+}
+
+/// 単色の背景
+pub struct SolidBackground {
+    color: ColorGlam,
+}
+
+impl SolidBackground {
+    pub fn new(color: ColorGlam) -> Self {
+        SolidBackground { color }
+    }
+}
+
+impl Background for SolidBackground {
+    fn radiance(&self, _direction: Vec3Glam) -> ColorGlam {
+        self.color
+    }
+}
+
+/// 下方向から上方向へ2色を補間する垂直グラデーションの背景
+pub struct GradientBackground {
+    bottom: ColorGlam,
+    top: ColorGlam,
+}
+
+impl GradientBackground {
+    /// 新しいグラデーション背景を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `bottom` - 真下方向の色
+    /// * `top` - 真上方向の色
+    pub fn new(bottom: ColorGlam, top: ColorGlam) -> Self {
+        GradientBackground { bottom, top }
+    }
+}
+
+impl Default for GradientBackground {
+    /// 白から空色へのデフォルトのグラデーション
+    fn default() -> Self {
+        Self::new(ColorGlam::new(1.0, 1.0, 1.0), ColorGlam::new(0.5, 0.7, 1.0))
+    }
+}
+
+impl Background for GradientBackground {
+    fn radiance(&self, direction: Vec3Glam) -> ColorGlam {
+        let unit_direction = direction.unit_vector();
+        let t = 0.5 * (unit_direction.y() + 1.0);
+        self.bottom * (1.0 - t) + self.top * t
+    }
+}
+
+/// スクリーン空間に貼り付ける背景画像（バックプレート）
+///
+/// カメラレイからのみ見え、ライティングには寄与しない
+pub struct Backplate {
+    image: ImageData,
+}
+
+impl Backplate {
+    pub fn new(image: ImageData) -> Self {
+        Backplate { image }
+    }
+
+    /// スクリーン座標に対応する画像の色を取得
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - 水平方向の位置（0.0 ~ 1.0、左端が0）
+    /// * `t` - 垂直方向の位置（0.0 ~ 1.0、下端が0）
+    pub fn color(&self, s: f64, t: f64) -> ColorGlam {
+        let width = self.image.width();
+        let height = self.image.height();
+
+        let x = ((s.clamp(0.0, 1.0) * width as f64) as u32).min(width - 1);
+        let y = (((1.0 - t.clamp(0.0, 1.0)) * height as f64) as u32).min(height - 1);
+
+        self.image.pixel(x, y)
+    }
+}
@@ -9,9 +9,10 @@ pub struct Camera {
     lower_left_corner: Vec3Glam,
     horizontal: Vec3Glam,
     vertical: Vec3Glam,
-    u: Vec3Glam,      // カメラ座標系のx軸
-    v: Vec3Glam,      // カメラ座標系のy軸
-    w: Vec3Glam,      // カメラ座標系のz軸
+    u: Vec3Glam, // カメラ座標系のx軸
+    v: Vec3Glam, // カメラ座標系のy軸
+    #[allow(dead_code)]
+    w: Vec3Glam, // カメラ座標系のz軸
     lens_radius: f64, // レンズの半径
 }
 
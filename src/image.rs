@@ -19,3 +19,94 @@ pub fn write_ppm(path: &str, width: u32, height: u32, pixels: &[ColorGlam]) -> i
 
     Ok(())
 }
+
+/// アルファチャンネル付きのPNG画像を書き込む
+///
+/// # Arguments
+///
+/// * `pixels` - アルファを乗算済みの色
+/// * `alphas` - ピクセルごとのアルファ（0.0 ~ 1.0）
+pub fn write_png_rgba(
+    path: &str,
+    width: u32,
+    height: u32,
+    pixels: &[ColorGlam],
+    alphas: &[f64],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut buffer = ::image::RgbaImage::new(width, height);
+    for (index, (pixel, &alpha)) in pixels.iter().zip(alphas).enumerate() {
+        // PNGはアルファを乗算しない形式のため、色をアルファで割り戻す
+        let alpha = alpha.clamp(0.0, 1.0);
+        let color = if alpha > 0.0 {
+            *pixel / alpha
+        } else {
+            ColorGlam::new(0.0, 0.0, 0.0)
+        };
+        let (r, g, b) = color.to_rgb();
+        let a = (256.0 * alpha.min(0.999)) as u8;
+        let (x, y) = (index as u32 % width, index as u32 / width);
+        buffer.put_pixel(x, y, ::image::Rgba([r, g, b, a]));
+    }
+    buffer.save(path)?;
+    Ok(())
+}
+
+/// 読み込んだ画像のピクセルデータ
+pub struct ImageData {
+    width: u32,
+    height: u32,
+    pixels: Vec<ColorGlam>,
+}
+
+impl ImageData {
+    pub fn new(width: u32, height: u32, pixels: Vec<ColorGlam>) -> Self {
+        ImageData {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// 指定座標のピクセル値を取得（左上が原点）
+    pub fn pixel(&self, x: u32, y: u32) -> ColorGlam {
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
+
+/// 画像ファイルを読み込む（PNG/JPEG/PPM/HDRに対応）
+///
+/// 値は0.0 ~ 1.0に正規化されるが、色空間の変換は行わない
+pub fn load_image(path: &str) -> Result<ImageData, Box<dyn std::error::Error>> {
+    let decoded = ::image::open(path)?.into_rgb32f();
+    let (width, height) = decoded.dimensions();
+
+    let pixels = decoded
+        .pixels()
+        .map(|p| ColorGlam::new(p[0] as f64, p[1] as f64, p[2] as f64))
+        .collect();
+
+    Ok(ImageData::new(width, height, pixels))
+}
+
+/// 画像ファイルのアルファチャンネルをグレースケール画像として読み込む
+///
+/// アルファチャンネルを持たない画像はすべて1.0（不透明）になる
+pub fn load_image_alpha(path: &str) -> Result<ImageData, Box<dyn std::error::Error>> {
+    let decoded = ::image::open(path)?.into_rgba32f();
+    let (width, height) = decoded.dimensions();
+
+    let pixels = decoded
+        .pixels()
+        .map(|p| ColorGlam::new(p[3] as f64, p[3] as f64, p[3] as f64))
+        .collect();
+
+    Ok(ImageData::new(width, height, pixels))
+}
@@ -0,0 +1,583 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::background::{Background, Backplate};
+use crate::light::LightList;
+use crate::ray::{Ray, RayKind, RayMedium};
+use crate::sky::PreethamSky;
+use crate::types::{HitRecord, Hittable, Matte, random_unit_vector};
+use crate::vec3_glam::{ColorGlam, Vec3Glam};
+use crate::volumetric::{Medium, MediumEvent, VolumetricMedium};
+
+/// シーン全体の情報を保持し、レイの色を計算する
+pub struct Integrator {
+    world: Arc<dyn Hittable>,
+    volumetric: Option<Box<dyn VolumetricMedium>>,
+    sky: Option<Arc<PreethamSky>>,
+    lights: LightList,
+    background: Arc<dyn Background>,
+    backplate: Option<Backplate>,
+    max_depth: i32,
+    /// カメラから直接見える背景を透明（黒、アルファ0）にするか
+    transparent_background: bool,
+    /// シーン内の範囲に存在する関与媒質（雲、煙など）
+    medium: Option<Arc<dyn Medium>>,
+}
+
+/// 媒質内のランダムウォークで許容する散乱の最大回数
+const MAX_MEDIUM_EVENTS: usize = 256;
+
+/// シャドウレイやカメラレイが通過できる表面（媒質の境界や薄い板）の最大数
+const MAX_CROSSINGS: usize = 16;
+
+impl Integrator {
+    /// 新しいインテグレータを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `world` - シーンのオブジェクト
+    /// * `volumetric` - シーン全体に適用するボリューメトリック効果
+    /// * `sky` - 太陽ディスクを背景に描画する天空モデル
+    /// * `lights` - 直接光サンプリングの対象となる光源
+    /// * `background` - ライティングに使用する背景
+    /// * `backplate` - カメラレイにのみ見える背景画像
+    /// * `max_depth` - 反射の最大回数
+    pub fn new(
+        world: Arc<dyn Hittable>,
+        volumetric: Option<Box<dyn VolumetricMedium>>,
+        sky: Option<Arc<PreethamSky>>,
+        lights: LightList,
+        background: Arc<dyn Background>,
+        backplate: Option<Backplate>,
+        max_depth: i32,
+    ) -> Self {
+        Integrator {
+            world,
+            volumetric,
+            sky,
+            lights,
+            background,
+            backplate,
+            max_depth,
+            transparent_background: false,
+            medium: None,
+        }
+    }
+
+    /// シーン内の関与媒質を設定（散乱点でも直接光を評価するボリュームのパストレーシング）
+    pub fn with_medium(mut self, medium: Option<Arc<dyn Medium>>) -> Self {
+        self.medium = medium;
+        self
+    }
+
+    /// カメラから直接見える背景を透明にする（合成用）
+    pub fn with_transparent_background(mut self, transparent: bool) -> Self {
+        self.transparent_background = transparent;
+        self
+    }
+
+    /// カメラレイの色とアルファを計算
+    ///
+    /// 色はアルファを乗算済みの値で、背景を透明にしない場合は背景と合成済みの値
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - カメラから射出したレイ
+    /// * `s` - スクリーン上の水平方向の位置（0.0 ~ 1.0）
+    /// * `t` - スクリーン上の垂直方向の位置（0.0 ~ 1.0）
+    pub fn camera_ray_color(&self, ray: &Ray, s: f64, t: f64) -> (ColorGlam, f64) {
+        self.camera_path(ray, Some((s, t)), MAX_CROSSINGS)
+    }
+
+    /// カメラから見える色とアルファを、媒質の境界を越えながら計算
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - カメラレイ（媒質の境界を越えた後はその先へ進むレイ）
+    /// * `screen` - スクリーン上の位置
+    /// * `crossings` - さらに越えられる媒質の境界の数
+    fn camera_path(
+        &self,
+        ray: &Ray,
+        screen: Option<(f64, f64)>,
+        crossings: usize,
+    ) -> (ColorGlam, f64) {
+        let hit = self.world.hit(ray, 0.001, f64::INFINITY);
+
+        // 関与媒質で散乱した場合は媒質の色とする（アルファは1）
+        let mut throughput = ColorGlam::new(1.0, 1.0, 1.0);
+        let mut emission = ColorGlam::new(0.0, 0.0, 0.0);
+        if let Some(medium) = self.medium_in(ray.medium()) {
+            emission = medium.emission(ray, Self::hit_distance(&hit));
+            match medium.sample(ray, Self::hit_distance(&hit), throughput) {
+                MediumEvent::Scatter { t, weight } => {
+                    return (
+                        emission + self.medium_walk(ray, medium, t, weight, self.max_depth),
+                        1.0,
+                    );
+                }
+                MediumEvent::Pass { weight } => throughput = weight,
+            }
+        }
+
+        let (color, alpha) = self.surface_with_matte(ray, hit, screen, crossings);
+        (emission + throughput * color, alpha)
+    }
+
+    /// カメラから直接見えた表面の色とアルファを計算（合成用のマットを考慮）
+    fn surface_with_matte(
+        &self,
+        ray: &Ray,
+        hit: Option<HitRecord>,
+        screen: Option<(f64, f64)>,
+        crossings: usize,
+    ) -> (ColorGlam, f64) {
+        let Some(rec) = hit else {
+            return (self.behind_matte(ray, screen), 0.0);
+        };
+
+        // 媒質の境界そのものは見えないため、カメラレイを境界の先の媒質へ進める。
+        // 媒質内で散乱した場合のみ不透明になるため、アルファの期待値は 1 - 透過率 となる
+        if rec.material.is_interface(&rec) && crossings > 0 {
+            let through = Ray::new(rec.point, ray.direction())
+                .with_cone(ray.cone_width_at(rec.t), ray.cone_spread())
+                .with_medium(Self::interior_towards(ray, &rec, ray.direction()));
+            return self.camera_path(&through, screen, crossings - 1);
+        }
+
+        match rec.material.matte(&rec) {
+            None => (self.shade(ray, &rec, self.max_depth, true), 1.0),
+            Some(Matte::Holdout) => (self.behind_matte(ray, screen), 0.0),
+            Some(Matte::ShadowCatcher { reflection }) => {
+                let (color, alpha) = self.shadow_catcher(ray, &rec, reflection);
+                (
+                    color + self.behind_matte(ray, screen) * (1.0 - alpha),
+                    alpha,
+                )
+            }
+        }
+    }
+
+    /// 透明な部分の背後に見える色（背景を透明にする場合は黒）
+    fn behind_matte(&self, ray: &Ray, screen: Option<(f64, f64)>) -> ColorGlam {
+        if self.transparent_background {
+            ColorGlam::new(0.0, 0.0, 0.0)
+        } else {
+            self.background_color(ray, true, screen)
+        }
+    }
+
+    /// 影受けの表面での影の濃さと映り込みを計算
+    ///
+    /// 遮蔽物を無視した場合とした場合の照度の比から影の濃さを求める（媒質の境界や
+    /// 煙を通る光は透過率の輝度の分だけ届く）。
+    /// 戻り値は映り込みの色（アルファ乗算済み）とアルファ
+    fn shadow_catcher(&self, ray: &Ray, rec: &HitRecord, reflection: f64) -> (ColorGlam, f64) {
+        let mut unoccluded = 0.0;
+        let mut visible = 0.0;
+
+        // 光源からの直接光（完全拡散面として評価）
+        if let Some((index, light)) = self.lights.sample(rec.point, rec.normal)
+            && rec
+                .light_links
+                .as_ref()
+                .is_none_or(|links| links.allows(index))
+        {
+            let cosine = rec.normal.dot(&light.direction).max(0.0);
+            let irradiance = light.radiance.luminance() * cosine;
+            unoccluded += irradiance;
+            let interior = Self::interior_towards(ray, rec, light.direction);
+            visible += irradiance
+                * self
+                    .transmittance(rec.point, light.direction, light.distance, interior)
+                    .luminance();
+        }
+
+        // 背景からの光（コサイン重み付きで方向を選ぶ）。
+        // 確率密度は cosθ/π なので、照度の推定値は π・L となり光源の項と単位がそろう
+        let mut direction = rec.normal + random_unit_vector();
+        if direction.length_squared() < 1e-8 {
+            direction = rec.normal;
+        }
+        let direction = direction.unit_vector();
+        let irradiance = PI * self.background.radiance(direction).luminance();
+        unoccluded += irradiance;
+        let interior = Self::interior_towards(ray, rec, direction);
+        visible += irradiance
+            * self
+                .transmittance(rec.point, direction, f64::INFINITY, interior)
+                .luminance();
+
+        let shadow = if unoccluded > 0.0 {
+            (1.0 - visible / unoccluded).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        // 周囲の物体の映り込み（背景の映り込みは背景画像に含まれているため記録しない）
+        let mut reflected = ColorGlam::new(0.0, 0.0, 0.0);
+        let mut reflection_alpha = 0.0;
+        if reflection > 0.0 {
+            let mirror = Ray::new(
+                rec.point,
+                ray.direction().unit_vector().reflect(&rec.normal),
+            )
+            .with_kind(RayKind::Secondary)
+            .with_cone(ray.cone_width_at(rec.t), ray.cone_spread());
+            if let Some(hit) = self.world.hit(&mirror, 0.001, f64::INFINITY) {
+                reflected = self.shade(&mirror, &hit, self.max_depth - 1, true) * reflection;
+                reflection_alpha = reflection;
+            }
+        }
+
+        (
+            reflected,
+            shadow + reflection_alpha - shadow * reflection_alpha,
+        )
+    }
+
+    /// 光源までの透過率（遮蔽物があれば0、関与媒質を通る場合はその透過率）
+    ///
+    /// 光を透過させる表面（媒質の境界や薄い板）はその透過率を掛けて通過し、
+    /// 媒質の境界を越えるたびに媒質を切り替える
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - シャドウレイの原点
+    /// * `direction` - 光源へ向かう単位ベクトル
+    /// * `distance` - 光源までの距離
+    /// * `interior` - 原点がある空間の媒質
+    fn transmittance(
+        &self,
+        origin: Vec3Glam,
+        direction: Vec3Glam,
+        distance: f64,
+        interior: RayMedium,
+    ) -> ColorGlam {
+        let mut transmittance = ColorGlam::new(1.0, 1.0, 1.0);
+        let mut origin = origin;
+        let mut remaining = distance;
+        let mut interior = interior;
+
+        for _ in 0..MAX_CROSSINGS {
+            let shadow_ray = Ray::new(origin, direction)
+                .with_kind(RayKind::Shadow)
+                .with_medium(interior);
+            let hit = self.world.hit(&shadow_ray, 0.001, remaining - 0.001);
+            if let Some(medium) = self.medium_in(shadow_ray.medium()) {
+                let end = Self::hit_distance(&hit).min(remaining);
+                transmittance = transmittance * medium.transmittance(&shadow_ray, end);
+            }
+
+            let Some(rec) = hit else {
+                return transmittance;
+            };
+            let Some(pass) = rec.material.shadow_transmittance(&rec, direction) else {
+                break;
+            };
+            transmittance = transmittance * pass;
+            interior = Self::interior_towards(&shadow_ray, &rec, direction);
+            origin = rec.point;
+            remaining -= rec.t;
+        }
+
+        ColorGlam::new(0.0, 0.0, 0.0)
+    }
+
+    /// 表面から方向directionへ進むレイが入る空間の媒質
+    ///
+    /// 入射側へ戻る方向や厚みのない面を透過する方向では入射レイの媒質を引き継ぐ。
+    /// 境界を内側へ越える場合は物体内部の媒質（媒質のない物体では真空）、
+    /// 外側へ越える場合はシーン内の媒質とする。媒質の入れ子は扱わない
+    fn interior_towards(ray: &Ray, rec: &HitRecord, direction: Vec3Glam) -> RayMedium {
+        if direction.dot(&rec.normal) > 0.0 || rec.material.is_thin(rec) {
+            ray.medium().clone()
+        } else if rec.front_face {
+            rec.medium
+                .clone()
+                .map_or(RayMedium::Vacuum, RayMedium::Object)
+        } else {
+            RayMedium::Scene
+        }
+    }
+
+    /// レイが進む空間で光と相互作用する媒質（真空の場合はNone）
+    fn medium_in<'a>(&'a self, medium: &'a RayMedium) -> Option<&'a Arc<dyn Medium>> {
+        match medium {
+            RayMedium::Scene => self.medium.as_ref(),
+            RayMedium::Object(medium) => Some(medium),
+            RayMedium::Vacuum => None,
+        }
+    }
+
+    /// 光源を1つ選んで直接光を計算
+    fn sample_lights(&self, ray: &Ray, rec: &HitRecord) -> ColorGlam {
+        let black = ColorGlam::new(0.0, 0.0, 0.0);
+
+        let Some((index, light)) = self.lights.sample(rec.point, rec.normal) else {
+            return black;
+        };
+        // ライトリンクで除外された光源は寄与しない
+        if let Some(light_links) = &rec.light_links
+            && !light_links.allows(index)
+        {
+            return black;
+        }
+        let Some(bsdf) = rec.material.eval(ray, rec, light.direction) else {
+            return black;
+        };
+        if bsdf.luminance() <= 0.0 {
+            return black;
+        }
+
+        // シャドウレイで光源までの遮蔽と媒質による減衰を確認
+        let interior = Self::interior_towards(ray, rec, light.direction);
+        bsdf * light.radiance
+            * self.transmittance(rec.point, light.direction, light.distance, interior)
+    }
+
+    /// 媒質内の散乱点で光源を1つ選んで直接光を計算
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - 散乱点
+    /// * `direction` - 散乱点に入射したレイの進行方向
+    /// * `medium` - 散乱点の媒質
+    /// * `interior` - 散乱点がある空間の媒質
+    fn sample_lights_in_medium(
+        &self,
+        point: Vec3Glam,
+        direction: Vec3Glam,
+        medium: &Arc<dyn Medium>,
+        interior: RayMedium,
+    ) -> ColorGlam {
+        let Some((_, light)) = self.lights.sample(point, Vec3Glam::new(0.0, 0.0, 0.0)) else {
+            return ColorGlam::new(0.0, 0.0, 0.0);
+        };
+        let phase = medium.phase().evaluate(direction, light.direction);
+        if phase <= 0.0 {
+            return ColorGlam::new(0.0, 0.0, 0.0);
+        }
+        light.radiance
+            * phase
+            * self.transmittance(point, light.direction, light.distance, interior)
+    }
+
+    /// 関与媒質の中で散乱したレイの色を計算
+    ///
+    /// 散乱点ごとに直接光と次の区間の媒質の放射を加えながら方向を選び直してランダムウォークし、
+    /// 散乱せずに表面または背景へ到達した時点でその色を加える。
+    /// 散乱の回数が多くなるため、媒質内の散乱は再帰せず反射回数にも数えない
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - 媒質内を進むレイ
+    /// * `medium` - レイが進む媒質
+    /// * `t` - 最初の散乱点のレイのパラメータ
+    /// * `weight` - 最初の散乱点までの経路の重み
+    fn medium_walk(
+        &self,
+        ray: &Ray,
+        medium: &Arc<dyn Medium>,
+        t: f64,
+        weight: ColorGlam,
+        depth: i32,
+    ) -> ColorGlam {
+        let mut color = ColorGlam::new(0.0, 0.0, 0.0);
+        let mut throughput = ColorGlam::new(1.0, 1.0, 1.0);
+        let spectral = Self::spectral_weight(ray);
+        let mut current = ray.clone();
+        let (mut t, mut weight) = (t, weight);
+
+        for _ in 0..MAX_MEDIUM_EVENTS {
+            throughput = throughput * weight;
+            let point = current.at(t);
+            let direction = current.direction().unit_vector();
+            if !medium.is_enclosed() {
+                color = color
+                    + throughput
+                        * spectral
+                        * self.sample_lights_in_medium(
+                            point,
+                            direction,
+                            medium,
+                            ray.medium().clone(),
+                        );
+            }
+
+            current = Ray::new(point, medium.phase().sample(direction))
+                .with_kind(RayKind::Secondary)
+                .with_cone(current.cone_width_at(t), ray.cone_spread())
+                .with_wavelengths(ray.wavelengths().copied())
+                .with_medium(ray.medium().clone());
+            let hit = self.world.hit(&current, 0.001, f64::INFINITY);
+            color =
+                color + throughput * spectral * medium.emission(&current, Self::hit_distance(&hit));
+            match medium.sample(&current, Self::hit_distance(&hit), throughput) {
+                MediumEvent::Scatter {
+                    t: next_t,
+                    weight: next_weight,
+                } => (t, weight) = (next_t, next_weight),
+                MediumEvent::Pass { weight } => {
+                    // 太陽ディスクは散乱点の直接光サンプリングで数えている
+                    return color
+                        + throughput
+                            * weight
+                            * self.surface_color(&current, hit, depth, false, None);
+                }
+            }
+        }
+
+        color
+    }
+
+    /// パスが追跡する波長の重みをRGBに投影した値（波長の組がないRGBのパスでは白）
+    ///
+    /// 波長ごとの係数は散乱レイの波長の組で運ぶため、経路上で加える放射輝度にはこれを掛ける
+    fn spectral_weight(ray: &Ray) -> ColorGlam {
+        ray.wavelengths()
+            .map_or(ColorGlam::new(1.0, 1.0, 1.0), |w| w.rgb_weight())
+    }
+
+    /// 衝突点までのレイのパラメータ（当たらない場合は無限大）
+    fn hit_distance(hit: &Option<HitRecord>) -> f64 {
+        hit.as_ref().map_or(f64::INFINITY, |rec| rec.t)
+    }
+
+    fn ray_color(
+        &self,
+        ray: &Ray,
+        depth: i32,
+        count_sun: bool,
+        screen: Option<(f64, f64)>,
+    ) -> ColorGlam {
+        // 反射回数が制限を超えた場合は黒を返す
+        if depth <= 0 {
+            return ColorGlam::new(0.0, 0.0, 0.0);
+        }
+
+        let hit = self.world.hit(ray, 0.001, f64::INFINITY);
+
+        // 物体の内部ではその媒質、外側ではシーン内の媒質の中を進む（媒質のない物体の内部は真空）
+        if let Some(medium) = self.medium_in(ray.medium()) {
+            let throughput = ColorGlam::new(1.0, 1.0, 1.0);
+            let emission =
+                medium.emission(ray, Self::hit_distance(&hit)) * Self::spectral_weight(ray);
+            return emission
+                + match medium.sample(ray, Self::hit_distance(&hit), throughput) {
+                    MediumEvent::Scatter { t, weight } => {
+                        self.medium_walk(ray, medium, t, weight, depth)
+                    }
+                    MediumEvent::Pass { weight } => {
+                        weight * self.surface_color(ray, hit, depth, count_sun, screen)
+                    }
+                };
+        }
+
+        self.surface_color(ray, hit, depth, count_sun, screen)
+    }
+
+    /// 表面または背景の色を計算
+    fn surface_color(
+        &self,
+        ray: &Ray,
+        hit: Option<HitRecord>,
+        depth: i32,
+        count_sun: bool,
+        screen: Option<(f64, f64)>,
+    ) -> ColorGlam {
+        match hit {
+            Some(rec) => self.shade(ray, &rec, depth, count_sun),
+            None => self.background_color(ray, count_sun, screen) * Self::spectral_weight(ray),
+        }
+    }
+
+    /// 表面に当たったレイの色を計算
+    ///
+    /// `count_sun`は入射レイが太陽ディスクを数えるかどうか（影を通す表面を
+    /// まっすぐ透過した散乱レイに引き継ぐ）
+    fn shade(&self, ray: &Ray, rec: &HitRecord, depth: i32, count_sun: bool) -> ColorGlam {
+        let spectral = Self::spectral_weight(ray);
+
+        // 発光する表面は光源リストに含まれないため、当たった時点で寄与を加える
+        let mut color = rec.material.emitted(rec) * spectral;
+
+        if let Some(scatter) = rec.material.scatter(ray, rec) {
+            // 散乱で波長の組が変わらなかった場合は入射レイの波長の組を引き継ぐ
+            let wavelengths = scatter
+                .scattered
+                .wavelengths()
+                .or(ray.wavelengths())
+                .copied();
+            // マテリアルが媒質を指定しない場合は境界を越えたかどうかで媒質を決める
+            let medium = match scatter.scattered.medium() {
+                RayMedium::Object(medium) => RayMedium::Object(medium.clone()),
+                _ => Self::interior_towards(ray, rec, scatter.scattered.direction()),
+            };
+            let scattered = scatter
+                .scattered
+                .with_medium(medium)
+                .with_kind(RayKind::Secondary)
+                .with_cone(ray.cone_width_at(rec.t), ray.cone_spread())
+                .with_wavelengths(wavelengths);
+            // 直接光サンプリングでカバーされる方向では太陽ディスクを二重に数えない。
+            // 影を通す表面をまっすぐ透過した場合は、手前の表面での直接光サンプリングが
+            // この先の太陽ディスクもカバーしているため、入射レイの扱いを引き継ぐ
+            let straight = scattered
+                .direction()
+                .unit_vector()
+                .dot(&ray.direction().unit_vector())
+                > 1.0 - 1e-6;
+            let count_sun = if straight
+                && rec
+                    .material
+                    .shadow_transmittance(rec, ray.direction())
+                    .is_some()
+            {
+                count_sun
+            } else {
+                scatter.specular
+            };
+            color = color
+                + scatter.attenuation * self.ray_color(&scattered, depth - 1, count_sun, None);
+        }
+
+        color = color + self.sample_lights(ray, rec) * spectral;
+
+        // ボリューメトリック効果の適用
+        if let Some(medium) = &self.volumetric {
+            let (scattered_light, transmittance) = medium.sample(ray, 0.0, rec.t);
+            color = color * transmittance + scattered_light * spectral;
+        }
+
+        color
+    }
+
+    /// どの物体にも当たらなかったレイの背景色を計算
+    fn background_color(
+        &self,
+        ray: &Ray,
+        count_sun: bool,
+        screen: Option<(f64, f64)>,
+    ) -> ColorGlam {
+        // カメラレイにはバックプレートを優先して表示
+        let background = match (&self.backplate, screen) {
+            (Some(backplate), Some((s, t))) => backplate.color(s, t),
+            _ => {
+                let mut radiance = self.background.radiance(ray.direction());
+                if let (Some(sky), true) = (&self.sky, count_sun) {
+                    radiance = radiance + sky.sun_radiance(ray.direction());
+                }
+                radiance
+            }
+        };
+
+        // 背景色にもボリューメトリック効果を適用
+        if let Some(medium) = &self.volumetric {
+            let (scattered_light, transmittance) = medium.sample(ray, 0.0, 1000.0); // 十分な距離
+            background * transmittance + scattered_light
+        } else {
+            background
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use crate::vec3_glam::Vec3Glam;
+
+/// IES LM-63形式の配光データ
+///
+/// タイプC配光（鉛直角0°が真下、水平角は真下を軸とした回転角）のみに対応する。
+/// 光度は倍率とバラスト係数を掛けた絶対値（cd）で保持し、実際の器具の明るさを再現する。
+///
+/// 水平角0°の面（C0面）は、ワールド座標の+X方向を配光の軸に垂直な面へ投影した向きとする
+/// （軸が±X方向の場合は+Z方向を使う）。水平角は器具の上から見て反時計回りに増える。
+/// 下向きの点光源ではC0面が+X、C90面が-Z方向となる。`with_rotation` で軸まわりに回転できる
+pub struct IesProfile {
+    vertical_angles: Vec<f64>,
+    horizontal_angles: Vec<f64>,
+    /// 水平角ごとの、鉛直角に対する光度
+    candela: Vec<Vec<f64>>,
+    /// 全方向の平均光度（cd）
+    average: f64,
+    /// C0面を軸まわりに回転する角度（度）
+    rotation: f64,
+}
+
+impl IesProfile {
+    /// IESファイルを読み込む
+    pub fn from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents).map_err(|e| format!("{}: {}", path, e).into())
+    }
+
+    /// IES LM-63形式の文字列を解析
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        // TILT行までのヘッダーとキーワードは読み飛ばす
+        let mut lines = contents.lines();
+        let tilt = lines
+            .by_ref()
+            .map(str::trim)
+            .find(|line| line.starts_with("TILT="))
+            .ok_or("TILT行が見つかりません")?;
+
+        let tokens: Vec<&str> = lines
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|token| !token.is_empty())
+            .collect();
+        let mut tokens = tokens.into_iter();
+
+        match tilt {
+            "TILT=NONE" => {}
+            "TILT=INCLUDE" => {
+                // ランプの傾きによる補正は使用しないため読み飛ばす。
+                // 壊れたファイルで個数が巨大な場合も、残りのデータ数を超えるならエラーとする
+                let _geometry = Self::next_value(&mut tokens)?;
+                let count = Self::next_value(&mut tokens)?;
+                let skip = (count >= 0.0 && count.fract() == 0.0)
+                    .then_some(count as usize)
+                    .and_then(|count| count.checked_mul(2))
+                    .filter(|&skip| skip <= tokens.len())
+                    .ok_or_else(|| format!("TILTのデータ数が不正です: {}", count))?;
+                for _ in 0..skip {
+                    tokens.next();
+                }
+            }
+            _ => return Err(format!("未対応のTILT指定です: {}", tilt)),
+        }
+        let mut next = || Self::next_value(&mut tokens);
+
+        let _lamp_count = next()?;
+        let _lumens_per_lamp = next()?;
+        let multiplier = next()?;
+        let vertical_count = next()? as usize;
+        let horizontal_count = next()? as usize;
+        let photometric_type = next()? as i32;
+        let _units = next()?;
+        let _width = next()?;
+        let _length = next()?;
+        let _height = next()?;
+        let ballast_factor = next()?;
+        let _ballast_lamp_factor = next()?;
+        let _input_watts = next()?;
+
+        if photometric_type != 1 {
+            return Err(format!(
+                "タイプC以外の配光には対応していません: {}",
+                photometric_type
+            ));
+        }
+        if vertical_count == 0 || horizontal_count == 0 {
+            return Err("角度の数が0です".to_string());
+        }
+
+        let vertical_angles = (0..vertical_count)
+            .map(|_| next())
+            .collect::<Result<Vec<_>, _>>()?;
+        let horizontal_angles = (0..horizontal_count)
+            .map(|_| next())
+            .collect::<Result<Vec<_>, _>>()?;
+        let candela = (0..horizontal_count)
+            .map(|_| {
+                (0..vertical_count)
+                    .map(|_| next().map(|c| c * multiplier * ballast_factor))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let max = candela.iter().flatten().cloned().fold(0.0, f64::max);
+        if max <= 0.0 {
+            return Err("光度がすべて0です".to_string());
+        }
+
+        let mut profile = IesProfile {
+            vertical_angles,
+            horizontal_angles,
+            candela,
+            average: 0.0,
+            rotation: 0.0,
+        };
+        profile.average = profile.compute_average();
+
+        Ok(profile)
+    }
+
+    /// C0面を配光の軸まわりに回転する（器具の上から見て反時計回り、度）
+    pub fn with_rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees;
+        self
+    }
+
+    /// 次の数値を読み込む
+    fn next_value<'a>(tokens: &mut impl Iterator<Item = &'a str>) -> Result<f64, String> {
+        let token = tokens.next().ok_or("データが途中で終わっています")?;
+        token
+            .parse::<f64>()
+            .map_err(|_| format!("数値として解析できません: {}", token))
+    }
+
+    /// 立体角で重み付けした全方向の平均光度
+    fn compute_average(&self) -> f64 {
+        let steps = 64;
+        let mut sum = 0.0;
+        let mut weight = 0.0;
+        for i in 0..steps {
+            let theta = (i as f64 + 0.5) / steps as f64 * 180.0;
+            let sin_theta = theta.to_radians().sin();
+            for j in 0..steps {
+                let phi = (j as f64 + 0.5) / steps as f64 * 360.0;
+                sum += self.lookup(theta, phi) * sin_theta;
+                weight += sin_theta;
+            }
+        }
+        sum / weight
+    }
+
+    /// 全方向の平均光度（cd）
+    pub fn average(&self) -> f64 {
+        self.average
+    }
+
+    /// 配光の基準座標系での方向に対する光度（cd）を評価
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - 光源から出ていく単位ベクトル
+    /// * `nadir` - 配光の鉛直角0°に対応する方向
+    pub fn evaluate(&self, direction: Vec3Glam, nadir: Vec3Glam) -> f64 {
+        let (c0, c90) = Self::c_plane_axes(nadir);
+        let theta = direction.dot(&nadir).clamp(-1.0, 1.0).acos().to_degrees();
+        let phi = direction.dot(&c90).atan2(direction.dot(&c0)).to_degrees();
+        self.lookup(theta, (phi - self.rotation).rem_euclid(360.0))
+    }
+
+    /// 回転前のC0面とC90面の向き（配光の軸に垂直な単位ベクトル）
+    fn c_plane_axes(nadir: Vec3Glam) -> (Vec3Glam, Vec3Glam) {
+        let reference = if nadir.x().abs() > 0.999 {
+            Vec3Glam::new(0.0, 0.0, 1.0)
+        } else {
+            Vec3Glam::new(1.0, 0.0, 0.0)
+        };
+        let c0 = (reference - nadir * nadir.dot(&reference)).unit_vector();
+        // 器具の上（軸の反対側）から見て反時計回りに90°
+        let c90 = (-1.0 * nadir).cross(&c0);
+        (c0, c90)
+    }
+
+    /// 鉛直角と水平角（度）から光度を双線形補間で求める
+    fn lookup(&self, theta: f64, phi: f64) -> f64 {
+        let phi = self.fold_horizontal(phi);
+
+        let (h0, h1, ht) = Self::bracket(&self.horizontal_angles, phi);
+        let (v0, v1, vt) = match Self::bracket_checked(&self.vertical_angles, theta) {
+            Some(bracket) => bracket,
+            // 定義範囲外の方向には光を放射しない
+            None => return 0.0,
+        };
+
+        let at = |h: usize| self.candela[h][v0] * (1.0 - vt) + self.candela[h][v1] * vt;
+        at(h0) * (1.0 - ht) + at(h1) * ht
+    }
+
+    /// 水平角の対称性に応じて角度をデータの範囲へ折り返す
+    fn fold_horizontal(&self, phi: f64) -> f64 {
+        let last = *self.horizontal_angles.last().unwrap();
+        if self.horizontal_angles.len() == 1 || last == 0.0 {
+            // 軸対称
+            0.0
+        } else if last <= 90.0 {
+            // 4象限対称
+            let phi = phi % 180.0;
+            if phi > 90.0 { 180.0 - phi } else { phi }
+        } else if last <= 180.0 {
+            // 左右対称
+            if phi > 180.0 { 360.0 - phi } else { phi }
+        } else {
+            phi
+        }
+    }
+
+    /// 角度を挟む2つのインデックスと補間係数を求める（範囲外は端の値を使用）
+    fn bracket(angles: &[f64], angle: f64) -> (usize, usize, f64) {
+        let last = angles.len() - 1;
+        if angle <= angles[0] {
+            return (0, 0, 0.0);
+        }
+        if angle >= angles[last] {
+            return (last, last, 0.0);
+        }
+        let upper = angles.partition_point(|&a| a <= angle).min(last);
+        let lower = upper - 1;
+        let span = angles[upper] - angles[lower];
+        let t = if span > 0.0 {
+            (angle - angles[lower]) / span
+        } else {
+            0.0
+        };
+        (lower, upper, t)
+    }
+
+    /// 角度を挟む2つのインデックスと補間係数を求める（範囲外はNone）
+    fn bracket_checked(angles: &[f64], angle: f64) -> Option<(usize, usize, f64)> {
+        let epsilon = 1e-6;
+        if angle < angles[0] - epsilon || angle > angles[angles.len() - 1] + epsilon {
+            return None;
+        }
+        Some(Self::bracket(angles, angle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 鉛直角3つ、水平角1つの軸対称な配光の数値部分
+    const PHOTOMETRY: &str = "1 1000 1 3 1 1 1 0 0 0 1 1 10\n0 45 90\n0\n100 50 0\n";
+
+    #[test]
+    fn parses_profile_without_tilt() {
+        let contents = format!("IESNA:LM-63-2002\nTILT=NONE\n{}", PHOTOMETRY);
+        let profile = IesProfile::parse(&contents).unwrap();
+        let down = Vec3Glam::new(0.0, -1.0, 0.0);
+        assert!((profile.evaluate(down, down) - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn orients_c0_plane_along_x_and_rotates_counter_clockwise() {
+        // C0面の方向だけに光を放射する非対称な配光
+        let contents = "TILT=NONE\n1 1000 1 2 5 1 1 0 0 0 1 1 10\n0 90\n0 90 180 270 360\n\
+                        100 100\n0 0\n0 0\n0 0\n100 100\n";
+        let profile = IesProfile::parse(contents).unwrap();
+        let down = Vec3Glam::new(0.0, -1.0, 0.0);
+        let towards_x = Vec3Glam::new(1.0, -1.0, 0.0).unit_vector();
+        let towards_minus_z = Vec3Glam::new(0.0, -1.0, -1.0).unit_vector();
+        assert!((profile.evaluate(towards_x, down) - 100.0).abs() < 1e-9);
+        assert!(profile.evaluate(towards_minus_z, down).abs() < 1e-9);
+
+        let rotated = IesProfile::parse(contents).unwrap().with_rotation(90.0);
+        assert!((rotated.evaluate(towards_minus_z, down) - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn skips_included_tilt_block() {
+        let contents = format!("TILT=INCLUDE\n1\n2\n0 90\n1 1\n{}", PHOTOMETRY);
+        assert!(IesProfile::parse(&contents).is_ok());
+    }
+
+    #[test]
+    fn rejects_tilt_count_beyond_data() {
+        let contents = format!("TILT=INCLUDE\n1\n1000\n0 90\n1 1\n{}", PHOTOMETRY);
+        assert!(IesProfile::parse(&contents).is_err());
+    }
+
+    #[test]
+    fn rejects_overflowing_tilt_count() {
+        let contents = format!("TILT=INCLUDE\n1\n1e30\n{}", PHOTOMETRY);
+        assert!(IesProfile::parse(&contents).is_err());
+    }
+
+    #[test]
+    fn rejects_negative_tilt_count() {
+        let contents = format!("TILT=INCLUDE\n1\n-2\n{}", PHOTOMETRY);
+        assert!(IesProfile::parse(&contents).is_err());
+    }
+}
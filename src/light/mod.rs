@@ -0,0 +1,243 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::aabb::AABB;
+use crate::vec3_glam::{ColorGlam, Vec3Glam};
+
+pub mod ies;
+pub mod point;
+pub mod sampler;
+pub mod spot;
+pub mod sun;
+
+pub use ies::IesProfile;
+pub use point::PointLight;
+pub use sampler::{BvhLightSampler, LightSampler, UniformLightSampler};
+pub use spot::SpotLight;
+pub use sun::SunLight;
+
+/// 光源のサンプリング結果
+pub struct LightSample {
+    /// 光源へ向かう単位ベクトル
+    pub direction: Vec3Glam,
+    /// 光源までの距離（無限遠光源はf64::INFINITY）
+    pub distance: f64,
+    /// 入射放射輝度を方向のサンプリング確率密度で割った値
+    pub radiance: ColorGlam,
+}
+
+/// 光源を表すトレイト
+pub trait Light: Send + Sync {
+    /// 指定位置に届く光をサンプリング
+    ///
+    /// 指定位置に光が届かない場合はNoneを返す
+    fn sample(&self, point: Vec3Glam) -> Option<LightSample>;
+
+    /// 光源BVHの構築に使用する境界情報
+    ///
+    /// 無限遠光源など空間的な範囲を持たない光源はNoneを返す
+    fn bounds(&self) -> Option<LightBounds>;
+}
+
+/// 光源の空間的な範囲と放射方向をまとめた境界情報
+#[derive(Clone, Copy)]
+pub struct LightBounds {
+    /// 光源を囲むバウンディングボックス
+    pub bounds: AABB,
+    /// 放射束（輝度換算）
+    pub power: f64,
+    /// 放射方向の中心軸
+    pub direction: Vec3Glam,
+    /// 放射方向の中心軸からの広がり（余弦）
+    pub cos_theta_o: f64,
+    /// 放射方向から更に光が届く角度の広がり（余弦）
+    pub cos_theta_e: f64,
+}
+
+impl LightBounds {
+    /// 全方向に放射する光源の境界情報
+    pub fn omnidirectional(bounds: AABB, power: f64) -> Self {
+        LightBounds {
+            bounds,
+            power,
+            direction: Vec3Glam::new(0.0, 1.0, 0.0),
+            cos_theta_o: -1.0,
+            cos_theta_e: (PI / 2.0).cos(),
+        }
+    }
+
+    /// 2つの境界情報を包含する境界情報を生成
+    pub fn union(a: &LightBounds, b: &LightBounds) -> LightBounds {
+        if a.power == 0.0 {
+            return *b;
+        }
+        if b.power == 0.0 {
+            return *a;
+        }
+
+        let (direction, cos_theta_o) =
+            union_cone(a.direction, a.cos_theta_o, b.direction, b.cos_theta_o);
+
+        LightBounds {
+            bounds: AABB::surrounding_box(&a.bounds, &b.bounds),
+            power: a.power + b.power,
+            direction,
+            cos_theta_o,
+            cos_theta_e: a.cos_theta_e.min(b.cos_theta_e),
+        }
+    }
+
+    /// 光源の重心
+    pub fn centroid(&self) -> Vec3Glam {
+        (self.bounds.min() + self.bounds.max()) * 0.5
+    }
+
+    /// 指定位置での光源の寄与の見積もり
+    ///
+    /// 距離による減衰と、放射方向・受光面の向きによる上限を考慮する
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - 受光点の位置
+    /// * `normal` - 受光点の法線（ゼロベクトルの場合は受光面の向きを考慮しない）
+    pub fn importance(&self, point: Vec3Glam, normal: Vec3Glam) -> f64 {
+        let center = self.centroid();
+        let diagonal = (self.bounds.max() - self.bounds.min()).length();
+        let to_point = point - center;
+        let distance_squared = to_point.length_squared().max(diagonal / 2.0).max(1e-6);
+        let wi = to_point / distance_squared.sqrt();
+
+        // θa - θb を [0, π] に制限した角度の余弦と正弦
+        let cos_sub_clamped = |sin_a: f64, cos_a: f64, sin_b: f64, cos_b: f64| {
+            if cos_a > cos_b {
+                1.0
+            } else {
+                cos_a * cos_b + sin_a * sin_b
+            }
+        };
+        let sin_sub_clamped = |sin_a: f64, cos_a: f64, sin_b: f64, cos_b: f64| {
+            if cos_a > cos_b {
+                0.0
+            } else {
+                sin_a * cos_b - cos_a * sin_b
+            }
+        };
+        let sin_of = |cos: f64| (1.0 - cos * cos).max(0.0).sqrt();
+
+        // 受光点から見たバウンディングボックスの見込み角
+        let radius_squared = (diagonal / 2.0).powi(2);
+        let cos_theta_b = if to_point.length_squared() < radius_squared {
+            -1.0
+        } else {
+            (1.0 - radius_squared / to_point.length_squared())
+                .max(0.0)
+                .sqrt()
+        };
+        let sin_theta_b = sin_of(cos_theta_b);
+
+        // 放射方向と受光点方向のなす角から、放射の広がりと見込み角を差し引く
+        let cos_theta_w = self.direction.dot(&wi);
+        let sin_theta_w = sin_of(cos_theta_w);
+        let sin_theta_o = sin_of(self.cos_theta_o);
+        let cos_theta_x = cos_sub_clamped(sin_theta_w, cos_theta_w, sin_theta_o, self.cos_theta_o);
+        let sin_theta_x = sin_sub_clamped(sin_theta_w, cos_theta_w, sin_theta_o, self.cos_theta_o);
+        let cos_theta_p = cos_sub_clamped(sin_theta_x, cos_theta_x, sin_theta_b, cos_theta_b);
+        if cos_theta_p <= self.cos_theta_e {
+            return 0.0;
+        }
+
+        // 受光面に対する入射角の余弦（媒質内の点など受光面がない場合は考慮しない）
+        let cos_theta_pi = if normal.length_squared() > 0.0 {
+            let cos_theta_i = wi.dot(&normal).abs();
+            let sin_theta_i = sin_of(cos_theta_i);
+            cos_sub_clamped(sin_theta_i, cos_theta_i, sin_theta_b, cos_theta_b)
+        } else {
+            1.0
+        };
+
+        (self.power * cos_theta_p * cos_theta_pi / distance_squared).max(0.0)
+    }
+}
+
+/// 2つの方向円錐を包含する円錐を計算
+fn union_cone(a: Vec3Glam, cos_a: f64, b: Vec3Glam, cos_b: f64) -> (Vec3Glam, f64) {
+    let whole_sphere = (Vec3Glam::new(0.0, 1.0, 0.0), -1.0);
+
+    let theta_a = cos_a.clamp(-1.0, 1.0).acos();
+    let theta_b = cos_b.clamp(-1.0, 1.0).acos();
+    let theta_d = a.dot(&b).clamp(-1.0, 1.0).acos();
+
+    // 一方の円錐がもう一方を含む場合
+    if (theta_d + theta_b).min(PI) <= theta_a {
+        return (a, cos_a);
+    }
+    if (theta_d + theta_a).min(PI) <= theta_b {
+        return (b, cos_b);
+    }
+
+    let theta_o = (theta_a + theta_d + theta_b) / 2.0;
+    if theta_o >= PI {
+        return whole_sphere;
+    }
+
+    // aの軸をbの方向へ回転させて新しい軸を求める
+    let theta_r = theta_o - theta_a;
+    let axis = a.cross(&b);
+    if axis.length_squared() < 1e-12 {
+        return whole_sphere;
+    }
+    let axis = axis.unit_vector();
+    let direction = a * theta_r.cos()
+        + axis.cross(&a) * theta_r.sin()
+        + axis * (axis.dot(&a) * (1.0 - theta_r.cos()));
+
+    (direction.unit_vector(), theta_o.cos())
+}
+
+/// オブジェクトを照らす光源の集合（ライトリンク）
+pub struct LightLinks {
+    enabled: Vec<bool>,
+}
+
+impl LightLinks {
+    /// 光源ごとの有効・無効の一覧から作成
+    pub fn new(enabled: Vec<bool>) -> Self {
+        LightLinks { enabled }
+    }
+
+    /// 指定したインデックスの光源がオブジェクトを照らすか
+    pub fn allows(&self, index: usize) -> bool {
+        self.enabled.get(index).copied().unwrap_or(true)
+    }
+}
+
+/// シーン内の光源と、その選択方法をまとめたもの
+pub struct LightList {
+    lights: Vec<Arc<dyn Light>>,
+    sampler: Box<dyn LightSampler>,
+}
+
+impl LightList {
+    pub fn new(lights: Vec<Arc<dyn Light>>, sampler: Box<dyn LightSampler>) -> Self {
+        LightList { lights, sampler }
+    }
+
+    /// 光源を1つ選択してサンプリング
+    ///
+    /// 返り値の放射輝度は光源の選択確率でも割られている
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - 受光点の位置
+    /// * `normal` - 受光点の法線（媒質内の散乱点ではゼロベクトル）
+    ///
+    /// # Returns
+    ///
+    /// * `(usize, LightSample)` - (選択した光源のインデックス, サンプリング結果)
+    pub fn sample(&self, point: Vec3Glam, normal: Vec3Glam) -> Option<(usize, LightSample)> {
+        let (index, pmf) = self.sampler.sample(point, normal)?;
+        let mut sample = self.lights[index].sample(point)?;
+        sample.radiance = sample.radiance / pmf;
+        Some((index, sample))
+    }
+}
@@ -0,0 +1,73 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use super::{IesProfile, Light, LightBounds, LightSample};
+use crate::aabb::AABB;
+use crate::vec3_glam::{ColorGlam, Vec3Glam};
+
+/// 全方向に均一に放射する点光源
+pub struct PointLight {
+    position: Vec3Glam,
+    /// 放射強度（色と強さの積）
+    intensity: ColorGlam,
+    /// 方向ごとの強度を変調する配光データ（鉛直角0°が真下）
+    profile: Option<Arc<IesProfile>>,
+}
+
+impl PointLight {
+    /// 新しい点光源を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - 光源の位置
+    /// * `color` - 光の色
+    /// * `intensity` - 光の強さ
+    pub fn new(position: Vec3Glam, color: ColorGlam, intensity: f64) -> Self {
+        PointLight {
+            position,
+            intensity: color * intensity,
+            profile: None,
+        }
+    }
+
+    /// IES配光データを設定
+    pub fn with_profile(mut self, profile: Arc<IesProfile>) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, point: Vec3Glam) -> Option<LightSample> {
+        let to_light = self.position - point;
+        let distance_squared = to_light.length_squared();
+        if distance_squared <= 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        let direction = to_light / distance;
+
+        let scale = match &self.profile {
+            Some(profile) => profile.evaluate(-direction, Vec3Glam::new(0.0, -1.0, 0.0)),
+            None => 1.0,
+        };
+        if scale <= 0.0 {
+            return None;
+        }
+
+        // 逆二乗の法則による減衰
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: self.intensity * (scale / distance_squared),
+        })
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let average = self.profile.as_ref().map_or(1.0, |p| p.average());
+        Some(LightBounds::omnidirectional(
+            AABB::new(self.position, self.position),
+            4.0 * PI * self.intensity.luminance() * average,
+        ))
+    }
+}
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use super::{Light, LightBounds};
+use crate::vec3_glam::Vec3Glam;
+
+/// サンプリングする光源を選択するトレイト
+pub trait LightSampler: Send + Sync {
+    /// 光源を1つ選択
+    ///
+    /// # Returns
+    ///
+    /// * `(usize, f64)` - (光源のインデックス, 選択確率)
+    fn sample(&self, point: Vec3Glam, normal: Vec3Glam) -> Option<(usize, f64)>;
+}
+
+/// すべての光源を等確率で選択する
+pub struct UniformLightSampler {
+    count: usize,
+}
+
+impl UniformLightSampler {
+    pub fn new(count: usize) -> Self {
+        UniformLightSampler { count }
+    }
+}
+
+impl LightSampler for UniformLightSampler {
+    fn sample(&self, _point: Vec3Glam, _normal: Vec3Glam) -> Option<(usize, f64)> {
+        if self.count == 0 {
+            return None;
+        }
+        let index = rand::thread_rng().gen_range(0..self.count);
+        Some((index, 1.0 / self.count as f64))
+    }
+}
+
+/// 光源BVHのノード
+enum LightNode {
+    Leaf {
+        bounds: LightBounds,
+        index: usize,
+    },
+    Interior {
+        bounds: LightBounds,
+        left: Box<LightNode>,
+        right: Box<LightNode>,
+    },
+}
+
+impl LightNode {
+    /// 光源のリストから再帰的にBVHを構築
+    fn build(mut lights: Vec<(usize, LightBounds)>) -> LightNode {
+        if lights.len() == 1 {
+            let (index, bounds) = lights[0];
+            return LightNode::Leaf { bounds, index };
+        }
+
+        // 重心の広がりが最も大きい軸で中央値により分割
+        let centroid_bounds = lights
+            .iter()
+            .map(|(_, b)| b.centroid())
+            .fold(None, |acc: Option<(Vec3Glam, Vec3Glam)>, c| match acc {
+                None => Some((c, c)),
+                Some((min, max)) => Some((
+                    Vec3Glam::new(min.x().min(c.x()), min.y().min(c.y()), min.z().min(c.z())),
+                    Vec3Glam::new(max.x().max(c.x()), max.y().max(c.y()), max.z().max(c.z())),
+                )),
+            })
+            .unwrap();
+        let extent = centroid_bounds.1 - centroid_bounds.0;
+        let axis = if extent.x() > extent.y() && extent.x() > extent.z() {
+            0
+        } else if extent.y() > extent.z() {
+            1
+        } else {
+            2
+        };
+
+        // 位置にNaNが含まれていてもパニックしないよう全順序で比較
+        lights.sort_by(|(_, a), (_, b)| a.centroid().get(axis).total_cmp(&b.centroid().get(axis)));
+        let right_lights = lights.split_off(lights.len() / 2);
+
+        let left = LightNode::build(lights);
+        let right = LightNode::build(right_lights);
+        let bounds = LightBounds::union(left.bounds(), right.bounds());
+
+        LightNode::Interior {
+            bounds,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    fn bounds(&self) -> &LightBounds {
+        match self {
+            LightNode::Leaf { bounds, .. } => bounds,
+            LightNode::Interior { bounds, .. } => bounds,
+        }
+    }
+}
+
+/// 受光点での寄与の見積もりに比例して光源を選択する光源BVH
+///
+/// 空間的な範囲を持たない無限遠光源はBVHとは別に等確率で選択する
+pub struct BvhLightSampler {
+    root: Option<LightNode>,
+    infinite_lights: Vec<usize>,
+}
+
+impl BvhLightSampler {
+    pub fn new(lights: &[Arc<dyn Light>]) -> Self {
+        let mut bounded_lights = Vec::new();
+        let mut infinite_lights = Vec::new();
+
+        for (index, light) in lights.iter().enumerate() {
+            match light.bounds() {
+                Some(bounds) if bounds.power > 0.0 => bounded_lights.push((index, bounds)),
+                Some(_) => {}
+                None => infinite_lights.push(index),
+            }
+        }
+
+        let root = if bounded_lights.is_empty() {
+            None
+        } else {
+            Some(LightNode::build(bounded_lights))
+        };
+
+        BvhLightSampler {
+            root,
+            infinite_lights,
+        }
+    }
+}
+
+impl LightSampler for BvhLightSampler {
+    fn sample(&self, point: Vec3Glam, normal: Vec3Glam) -> Option<(usize, f64)> {
+        let mut rng = rand::thread_rng();
+
+        // 無限遠光源とBVHのどちらから選ぶかを決める
+        let candidates = self.infinite_lights.len() + usize::from(self.root.is_some());
+        if candidates == 0 {
+            return None;
+        }
+        let infinite_probability = self.infinite_lights.len() as f64 / candidates as f64;
+        if rng.gen_range(0.0..1.0) < infinite_probability {
+            let index = rng.gen_range(0..self.infinite_lights.len());
+            return Some((
+                self.infinite_lights[index],
+                infinite_probability / self.infinite_lights.len() as f64,
+            ));
+        }
+
+        // 子ノードの重要度に比例した確率でBVHを辿る
+        let mut node = self.root.as_ref()?;
+        let mut pmf = 1.0 - infinite_probability;
+        loop {
+            match node {
+                LightNode::Leaf { bounds, index } => {
+                    return if bounds.importance(point, normal) > 0.0 {
+                        Some((*index, pmf))
+                    } else {
+                        None
+                    };
+                }
+                LightNode::Interior { left, right, .. } => {
+                    let left_importance = left.bounds().importance(point, normal);
+                    let right_importance = right.bounds().importance(point, normal);
+                    let total = left_importance + right_importance;
+                    if total <= 0.0 {
+                        return None;
+                    }
+
+                    let left_probability = left_importance / total;
+                    if rng.gen_range(0.0..1.0) < left_probability {
+                        pmf *= left_probability;
+                        node = left;
+                    } else {
+                        pmf *= 1.0 - left_probability;
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+}
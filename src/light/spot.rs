@@ -0,0 +1,109 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use super::{IesProfile, Light, LightBounds, LightSample};
+use crate::aabb::AABB;
+use crate::vec3_glam::{ColorGlam, Vec3Glam};
+
+/// 円錐状の範囲を照らすスポットライト
+pub struct SpotLight {
+    position: Vec3Glam,
+    direction: Vec3Glam,
+    /// 放射強度（色と強さの積）
+    intensity: ColorGlam,
+    /// 照射範囲の端の余弦
+    cos_total_width: f64,
+    /// 減衰が始まる角度の余弦
+    cos_falloff_start: f64,
+    /// 方向ごとの強度を変調する配光データ（鉛直角0°が照射方向）
+    profile: Option<Arc<IesProfile>>,
+}
+
+impl SpotLight {
+    /// 新しいスポットライトを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - 光源の位置
+    /// * `direction` - 照射方向
+    /// * `color` - 光の色
+    /// * `intensity` - 光の強さ
+    /// * `cone_angle` - 照射範囲の半角（度）
+    /// * `falloff_start` - 減衰が始まる角度（度）
+    pub fn new(
+        position: Vec3Glam,
+        direction: Vec3Glam,
+        color: ColorGlam,
+        intensity: f64,
+        cone_angle: f64,
+        falloff_start: f64,
+    ) -> Self {
+        SpotLight {
+            position,
+            direction: direction.unit_vector(),
+            intensity: color * intensity,
+            cos_total_width: cone_angle.to_radians().cos(),
+            cos_falloff_start: falloff_start.min(cone_angle).to_radians().cos(),
+            profile: None,
+        }
+    }
+
+    /// IES配光データを設定
+    pub fn with_profile(mut self, profile: Arc<IesProfile>) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// 照射方向からの角度による減衰（smoothstep）
+    fn falloff(&self, cos_theta: f64) -> f64 {
+        if cos_theta <= self.cos_total_width {
+            return 0.0;
+        }
+        if cos_theta >= self.cos_falloff_start {
+            return 1.0;
+        }
+        let t =
+            (cos_theta - self.cos_total_width) / (self.cos_falloff_start - self.cos_total_width);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, point: Vec3Glam) -> Option<LightSample> {
+        let to_light = self.position - point;
+        let distance_squared = to_light.length_squared();
+        if distance_squared <= 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        let direction = to_light / distance;
+
+        let mut falloff = self.falloff((-direction).dot(&self.direction));
+        if let Some(profile) = &self.profile {
+            falloff *= profile.evaluate(-direction, self.direction);
+        }
+        if falloff <= 0.0 {
+            return None;
+        }
+
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: self.intensity * (falloff / distance_squared),
+        })
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        // 照射範囲の立体角で近似した放射束
+        let solid_angle = 2.0 * PI * (1.0 - 0.5 * (self.cos_falloff_start + self.cos_total_width));
+        let average = self.profile.as_ref().map_or(1.0, |p| p.average());
+
+        Some(LightBounds {
+            bounds: AABB::new(self.position, self.position),
+            power: self.intensity.luminance() * solid_angle * average,
+            direction: self.direction,
+            cos_theta_o: self.cos_falloff_start,
+            cos_theta_e: (self.cos_total_width.acos() - self.cos_falloff_start.acos()).cos(),
+        })
+    }
+}
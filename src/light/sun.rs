@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use super::{Light, LightBounds, LightSample};
+use crate::sky::PreethamSky;
+use crate::vec3_glam::Vec3Glam;
+
+/// 天空モデルの太陽ディスクを光源として扱う
+pub struct SunLight {
+    sky: Arc<PreethamSky>,
+}
+
+impl SunLight {
+    pub fn new(sky: Arc<PreethamSky>) -> Self {
+        SunLight { sky }
+    }
+}
+
+impl Light for SunLight {
+    fn sample(&self, _point: Vec3Glam) -> Option<LightSample> {
+        let sun = self.sky.sample_sun()?;
+        Some(LightSample {
+            direction: sun.direction,
+            distance: f64::INFINITY,
+            radiance: sun.radiance,
+        })
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        None
+    }
+}
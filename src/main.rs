@@ -1,72 +1,572 @@
 mod aabb;
+mod background;
 mod bvh;
 mod camera;
 mod image;
+mod integrator;
+mod light;
 mod material;
 mod object;
 mod ray;
 mod scene;
+mod sky;
+mod spectrum;
+mod texture;
 mod types;
 mod vec3_glam;
 mod volumetric;
 
+use std::collections::HashMap;
 use std::env;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
-use crate::volumetric::{VolumetricMedium, fog::UniformFog};
+use crate::volumetric::{
+    DensityField, DoubleHenyeyGreenstein, HenyeyGreenstein, HeterogeneousMedium, HomogeneousMedium,
+    Isotropic, Medium, NoiseDensity, PhaseFunction, TextureDensity, VolumetricMedium, VoxelGrid,
+    fog::UniformFog,
+};
+use aabb::AABB;
+use background::{Background, Backplate, GradientBackground, SolidBackground};
 use camera::Camera;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use material::{Dielectric, Lambertian, Metal};
-use object::{HittableList, Sphere};
+use integrator::Integrator;
+use light::{
+    BvhLightSampler, IesProfile, Light, LightLinks, LightList, LightSampler, PointLight, SpotLight,
+    SunLight, UniformLightSampler,
+};
+use material::{
+    AlphaMasked, AlphaMode, Coated, ConductorFresnel, Dielectric, Holdout, Interface, Lambertian,
+    Metal, Mix, NormalMapped, NormalPerturbation, Principled, PrincipledParams, ShadowCatcher,
+    Sheen, Subsurface, ThinDielectric,
+};
+use object::{FilledObject, HittableList, LinkedObject, Sphere, Visibility};
 use rand::prelude::*;
-use ray::Ray;
 use rayon::prelude::*;
-use scene::{MaterialConfig, Scene, ShapeConfig, VolumetricConfig};
+use scene::{
+    AlphaModeConfig, BackgroundConfig, ColorConfig, DensityConfig, EmissionConfig,
+    ImageChannelConfig, LightConfig, LightLinksConfig, LightSamplerConfig, MaterialConfig,
+    MaterialKindConfig, MaterialLibraryConfig, MaterialRef, MediumConfig, MediumDensityConfig,
+    PhaseConfig, Scene, ShapeConfig, SkyConfig, TextureConfig, VolumetricConfig, WeightConfig,
+};
+use sky::PreethamSky;
+use texture::{
+    CheckerTexture, ImageFilter, ImageTexture, MarbleTexture, Noise, NoiseTexture, SolidColor,
+    Texture, WoodTexture,
+};
 use types::{Hittable, Material};
 use vec3_glam::ColorGlam;
 
-fn ray_color(
-    ray: &Ray,
-    world: &impl Hittable,
-    volumetric: Option<&dyn VolumetricMedium>,
-    depth: i32,
-) -> ColorGlam {
-    // 反射回数が制限を超えた場合は黒を返す
-    if depth <= 0 {
-        return ColorGlam::new(0.0, 0.0, 0.0);
+/// 色またはテクスチャの設定からテクスチャを生成
+fn build_texture(config: ColorConfig) -> Result<Arc<dyn Texture>, Box<dyn std::error::Error>> {
+    Ok(match config {
+        ColorConfig::Constant(color) => Arc::new(SolidColor::new(color.into())),
+        ColorConfig::Texture(TextureConfig::Solid { color }) => {
+            Arc::new(SolidColor::new(color.into()))
+        }
+        ColorConfig::Texture(TextureConfig::Image {
+            path,
+            wrap,
+            filter,
+            srgb,
+            channel,
+        }) => Arc::new(match channel {
+            ImageChannelConfig::Color => {
+                ImageTexture::load(&path, wrap.into(), filter.into(), srgb)?
+            }
+            ImageChannelConfig::Alpha => {
+                ImageTexture::load_alpha(&path, wrap.into(), filter.into())?
+            }
+        }),
+        ColorConfig::Texture(TextureConfig::Checker {
+            even,
+            odd,
+            scale,
+            space,
+        }) => Arc::new(CheckerTexture::new(
+            even.into(),
+            odd.into(),
+            scale,
+            space.into(),
+        )),
+        ColorConfig::Texture(TextureConfig::Noise {
+            low,
+            high,
+            scale,
+            noise,
+            octaves,
+            turbulence,
+            seed,
+        }) => Arc::new(NoiseTexture::new(
+            Noise::new(noise.into(), seed),
+            low.into(),
+            high.into(),
+            scale,
+            octaves,
+            turbulence,
+        )),
+        ColorConfig::Texture(TextureConfig::Marble {
+            base,
+            vein,
+            scale,
+            noise,
+            octaves,
+            distortion,
+            seed,
+        }) => Arc::new(MarbleTexture::new(
+            Noise::new(noise.into(), seed),
+            base.into(),
+            vein.into(),
+            scale,
+            octaves,
+            distortion,
+        )),
+        ColorConfig::Texture(TextureConfig::Wood {
+            light,
+            dark,
+            scale,
+            rings,
+            noise,
+            distortion,
+            seed,
+        }) => Arc::new(WoodTexture::new(
+            Noise::new(noise.into(), seed),
+            light.into(),
+            dark.into(),
+            scale,
+            rings,
+            distortion,
+        )),
+    })
+}
+
+/// 密度場の設定から密度場を生成
+fn build_density(
+    config: DensityConfig,
+) -> Result<Arc<dyn DensityField>, Box<dyn std::error::Error>> {
+    Ok(match config {
+        DensityConfig::Noise {
+            density,
+            scale,
+            octaves,
+            coverage,
+            noise,
+            seed,
+        } => Arc::new(NoiseDensity::new(
+            Noise::new(noise.into(), seed),
+            density,
+            scale,
+            octaves,
+            coverage,
+        )),
+        DensityConfig::Texture { texture, density } => {
+            Arc::new(TextureDensity::new(build_texture(texture)?, density))
+        }
+        DensityConfig::Grid { path, scale } => Arc::new(VoxelGrid::from_file(&path, scale)?),
+    })
+}
+
+/// 位相関数の設定から位相関数を生成
+fn build_phase(config: PhaseConfig) -> Arc<dyn PhaseFunction> {
+    match config {
+        PhaseConfig::Isotropic => Arc::new(Isotropic),
+        PhaseConfig::HenyeyGreenstein { g } => Arc::new(HenyeyGreenstein::new(g)),
+        PhaseConfig::DoubleHenyeyGreenstein {
+            forward,
+            backward,
+            weight,
+        } => Arc::new(DoubleHenyeyGreenstein::new(forward, backward, weight)),
     }
+}
 
-    if let Some(rec) = world.hit(ray, 0.001, f64::INFINITY) {
-        let mut color = ColorGlam::new(0.0, 0.0, 0.0);
+/// 密度場を持つ媒質を生成
+///
+/// # Arguments
+///
+/// * `density` - 密度場の設定
+/// * `absorption` - 密度1あたりの吸収係数
+/// * `scattering` - 密度1あたりの散乱係数
+/// * `bounds` - 媒質が存在する範囲（Noneの場合は密度場の範囲）
+/// * `phase` - 位相関数の設定
+/// * `emission` - 発光の設定
+fn build_heterogeneous(
+    density: DensityConfig,
+    absorption: ColorGlam,
+    scattering: ColorGlam,
+    bounds: Option<AABB>,
+    phase: PhaseConfig,
+    emission: Option<EmissionConfig>,
+) -> Result<HeterogeneousMedium, Box<dyn std::error::Error>> {
+    let density = build_density(density)?;
+    let bounds = bounds
+        .or(density.bounds())
+        .ok_or("密度場の範囲がないため、媒質の範囲（bounds）を指定してください")?;
+    let mut medium = HeterogeneousMedium::new(density, absorption, scattering, bounds)
+        .with_phase(build_phase(phase));
+    if let Some(emission) = emission {
+        medium = medium.with_emission(build_density(*emission.temperature)?, emission.intensity);
+    }
+    Ok(medium)
+}
 
-        if let Some(scatter) = rec.material.scatter(ray, &rec) {
-            color =
-                scatter.attenuation * ray_color(&scatter.scattered, world, volumetric, depth - 1);
+/// 物体内部の媒質の設定から媒質を生成
+///
+/// # Arguments
+///
+/// * `config` - 媒質の設定
+/// * `bounds` - 媒質を満たす物体の境界ボックス（密度場の範囲に使用）
+fn build_medium(
+    config: MediumConfig,
+    bounds: Option<AABB>,
+) -> Result<Arc<dyn Medium>, Box<dyn std::error::Error>> {
+    let absorption: ColorGlam = config
+        .absorption
+        .map_or(ColorGlam::new(0.0, 0.0, 0.0), Into::into);
+    let scattering: ColorGlam = config.scattering.into();
+    Ok(match config.density {
+        MediumDensityConfig::Constant(_) if config.emission.is_some() => {
+            return Err("発光（emission）には密度場を持つ媒質が必要です".into());
         }
+        MediumDensityConfig::Constant(density) => Arc::new(
+            HomogeneousMedium::new(absorption * density, scattering * density)
+                .with_phase(build_phase(config.phase)),
+        ),
+        MediumDensityConfig::Field(density) => Arc::new(build_heterogeneous(
+            density,
+            absorption,
+            scattering,
+            bounds,
+            config.phase,
+            config.emission,
+        )?),
+    })
+}
+
+/// 光源の強さを決める
+///
+/// IES配光の光度は絶対値（cd）のため、指定した強さはその倍率として扱い、省略時は1.0とする
+fn light_intensity(
+    intensity: Option<f64>,
+    ies: &Option<String>,
+) -> Result<f64, Box<dyn std::error::Error>> {
+    match (intensity, ies) {
+        (Some(intensity), _) => Ok(intensity),
+        (None, Some(_)) => Ok(1.0),
+        (None, None) => Err("IES配光を指定しない光源にはintensityが必要です".into()),
+    }
+}
+
+/// 名前付きマテリアルの集合
+///
+/// マテリアルは最初に参照された時点で生成し、同じ名前の参照ではそれを共有する。
+/// 参照されないマテリアル（ライブラリの未使用分など）は生成しない
+struct MaterialLibrary {
+    configs: HashMap<String, MaterialConfig>,
+    built: HashMap<String, Arc<dyn Material>>,
+    /// 生成中のマテリアルの名前（循環参照の検出用）
+    building: Vec<String>,
+}
 
-        // ボリューメトリック効果の適用
-        if let Some(medium) = volumetric {
-            let (scattered_light, transmittance) = medium.sample(ray, 0.0, rec.t);
-            color = color * transmittance + scattered_light;
+impl MaterialLibrary {
+    fn new(configs: HashMap<String, MaterialConfig>) -> Self {
+        MaterialLibrary {
+            configs,
+            built: HashMap::new(),
+            building: Vec::new(),
         }
+    }
 
-        return color;
+    /// インラインの定義または名前からマテリアルを取得
+    fn resolve(
+        &mut self,
+        reference: MaterialRef,
+    ) -> Result<Arc<dyn Material>, Box<dyn std::error::Error>> {
+        match reference {
+            MaterialRef::Inline(config) => build_material(*config, self),
+            MaterialRef::Named(name) => self.get(&name),
+        }
     }
 
-    let unit_direction = ray.direction().unit_vector();
-    let t = 0.5 * (unit_direction.y() + 1.0);
-    let background = ColorGlam::new(1.0, 1.0, 1.0) * (1.0 - t) + ColorGlam::new(0.5, 0.7, 1.0) * t;
+    /// 名前付きマテリアルを取得（未生成なら生成する）
+    fn get(&mut self, name: &str) -> Result<Arc<dyn Material>, Box<dyn std::error::Error>> {
+        if let Some(material) = self.built.get(name) {
+            return Ok(material.clone());
+        }
+        let Some(config) = self.configs.remove(name) else {
+            return Err(if self.building.iter().any(|n| n == name) {
+                format!(
+                    "マテリアルが循環参照しています: {}",
+                    self.building.join(" -> ")
+                )
+            } else {
+                format!("未定義のマテリアルが参照されています: {}", name)
+            }
+            .into());
+        };
 
-    // 背景色にもボリューメトリック効果を適用
-    if let Some(medium) = volumetric {
-        let (scattered_light, transmittance) = medium.sample(ray, 0.0, 1000.0); // 十分な距離
-        background * transmittance + scattered_light
-    } else {
-        background
+        self.building.push(name.to_string());
+        let material = build_material(config, self)?;
+        self.building.pop();
+        self.built.insert(name.to_string(), material.clone());
+        Ok(material)
     }
 }
 
+/// マテリアルの設定からマテリアルを生成
+fn build_material(
+    config: MaterialConfig,
+    library: &mut MaterialLibrary,
+) -> Result<Arc<dyn Material>, Box<dyn std::error::Error>> {
+    let mut material: Arc<dyn Material> = match config.kind {
+        MaterialKindConfig::Lambertian { albedo } => {
+            Arc::new(Lambertian::new(build_texture(albedo)?))
+        }
+        MaterialKindConfig::Metal {
+            albedo,
+            roughness,
+            preset,
+            eta,
+            k,
+            anisotropy,
+            anisotropy_rotation,
+            thin_film,
+        } => {
+            let mut metal = match (eta, k, preset, albedo) {
+                (Some(eta), Some(k), _, _) => Metal::with_fresnel(
+                    ConductorFresnel::Complex {
+                        eta: eta.into(),
+                        k: k.into(),
+                    },
+                    roughness,
+                ),
+                (None, None, Some(preset), _) => Metal::from_preset(preset.into(), roughness),
+                (None, None, None, Some(albedo)) => Metal::new(build_texture(albedo)?, roughness),
+                _ => {
+                    return Err(
+                        "Metalにはalbedo、preset、またはetaとkの組のいずれかを指定してください"
+                            .into(),
+                    );
+                }
+            }
+            .with_anisotropy(anisotropy, anisotropy_rotation.to_radians());
+            if let Some(thin_film) = thin_film {
+                metal = metal.with_thin_film(thin_film.into());
+            }
+            Arc::new(metal)
+        }
+        MaterialKindConfig::Dielectric {
+            ir,
+            roughness,
+            absorption,
+            transmittance,
+            transmittance_depth,
+            dispersion,
+            thin_film,
+        } => {
+            let mut dielectric = Dielectric::new(ir, roughness);
+            if let Some(dispersion) = dispersion {
+                dielectric = dielectric.with_dispersion(dispersion.into());
+            }
+            if let Some(thin_film) = thin_film {
+                dielectric = dielectric.with_thin_film(thin_film.into());
+            }
+            Arc::new(match (absorption, transmittance) {
+                (Some(absorption), None) => dielectric.with_absorption(absorption.into()),
+                (None, Some(transmittance)) => {
+                    dielectric.with_absorption(Dielectric::absorption_from_transmittance(
+                        transmittance.into(),
+                        transmittance_depth,
+                    ))
+                }
+                (None, None) => dielectric,
+                (Some(_), Some(_)) => {
+                    return Err(
+                        "Dielectricにはabsorptionとtransmittanceの一方のみを指定してください"
+                            .into(),
+                    );
+                }
+            })
+        }
+        MaterialKindConfig::Holdout => Arc::new(Holdout),
+        MaterialKindConfig::ShadowCatcher { albedo, reflection } => {
+            let albedo = match albedo {
+                Some(albedo) => build_texture(albedo)?,
+                None => Arc::new(SolidColor::new(ColorGlam::new(0.5, 0.5, 0.5))),
+            };
+            Arc::new(ShadowCatcher::new(albedo, reflection))
+        }
+        MaterialKindConfig::ThinDielectric { ir, film_thickness } => {
+            let mut thin = ThinDielectric::new(ir);
+            if let Some(thickness) = film_thickness {
+                thin = thin.with_film_thickness(thickness);
+            }
+            Arc::new(thin)
+        }
+        MaterialKindConfig::Principled {
+            base_color,
+            metallic,
+            roughness,
+            specular,
+            clearcoat,
+            clearcoat_roughness,
+            sheen,
+            sheen_tint,
+            transmission,
+            ior,
+            emission,
+            emission_strength,
+        } => {
+            let defaults = PrincipledParams::default();
+            let base_color = match base_color {
+                Some(c) => build_texture(c)?,
+                None => defaults.base_color,
+            };
+            // 放射色のみを指定した場合はその色で発光させる
+            let (emission, emission_strength) = match emission {
+                Some(c) => (build_texture(c)?, emission_strength),
+                None => (defaults.emission, 0.0),
+            };
+            Arc::new(Principled::new(PrincipledParams {
+                base_color,
+                metallic,
+                roughness,
+                specular,
+                clearcoat,
+                clearcoat_roughness,
+                sheen,
+                sheen_tint,
+                transmission,
+                ior,
+                emission,
+                emission_strength,
+            }))
+        }
+        MaterialKindConfig::Mix {
+            first,
+            second,
+            weight,
+        } => {
+            let weight: Arc<dyn Texture> = match weight {
+                WeightConfig::Constant(w) => Arc::new(SolidColor::new(ColorGlam::new(w, w, w))),
+                WeightConfig::Texture(texture) => build_texture(texture)?,
+            };
+            Arc::new(Mix::new(
+                library.resolve(first)?,
+                library.resolve(second)?,
+                weight,
+            ))
+        }
+        MaterialKindConfig::Coated {
+            base,
+            ir,
+            roughness,
+            tint,
+        } => {
+            let mut coated = Coated::new(library.resolve(base)?, ir, roughness);
+            if let Some(tint) = tint {
+                coated = coated.with_tint(tint.into());
+            }
+            Arc::new(coated)
+        }
+        MaterialKindConfig::Subsurface {
+            ir,
+            albedo,
+            mean_free_path,
+            absorption,
+            scattering,
+        } => {
+            let medium = match (albedo, mean_free_path, absorption, scattering) {
+                (Some(albedo), Some(mean_free_path), None, None) => {
+                    HomogeneousMedium::from_albedo(albedo.into(), mean_free_path.into())
+                }
+                (None, None, Some(absorption), Some(scattering)) => {
+                    HomogeneousMedium::new(absorption.into(), scattering.into())
+                }
+                _ => {
+                    return Err("Subsurfaceにはalbedoとmean_free_path、またはabsorptionとscatteringの組を指定してください".into());
+                }
+            };
+            Arc::new(Subsurface::new(medium, ir))
+        }
+    };
+
+    if let Some(sheen) = config.sheen_layer {
+        material = Arc::new(Sheen::new(
+            material,
+            build_texture(sheen.color)?,
+            sheen.roughness,
+        ));
+    }
+
+    // バンプマップを適用してから法線マップを適用する
+    if let Some(bump) = config.bump_map {
+        material = Arc::new(NormalMapped::new(
+            material,
+            NormalPerturbation::BumpMap {
+                texture: build_texture(bump.height)?,
+                strength: bump.strength,
+            },
+        ));
+    }
+    if let Some(normal) = config.normal_map {
+        // 法線マップは色ではないためsRGBの変換は行わない
+        let texture = ImageTexture::load(
+            &normal.path,
+            normal.wrap.into(),
+            ImageFilter::Bilinear,
+            Some(false),
+        )?;
+        material = Arc::new(NormalMapped::new(
+            material,
+            NormalPerturbation::NormalMap {
+                texture: Arc::new(texture),
+                strength: normal.strength,
+                flip_y: normal.flip_y,
+            },
+        ));
+    }
+
+    if let Some(opacity) = config.opacity {
+        let mode = match opacity.mode {
+            AlphaModeConfig::Threshold => AlphaMode::Threshold(opacity.threshold),
+            AlphaModeConfig::Stochastic => AlphaMode::Stochastic,
+        };
+        material = Arc::new(AlphaMasked::new(
+            material,
+            build_texture(opacity.texture)?,
+            mode,
+        ));
+    }
+
+    Ok(material)
+}
+
+/// ライトリンクの設定を光源のインデックスに対応付ける
+fn resolve_light_links(
+    config: &LightLinksConfig,
+    light_names: &[Option<String>],
+) -> Result<Arc<LightLinks>, Box<dyn std::error::Error>> {
+    for name in config.include.iter().flatten().chain(&config.exclude) {
+        if !light_names.iter().any(|n| n.as_ref() == Some(name)) {
+            return Err(format!("ライトリンクに未定義の光源が指定されています: {}", name).into());
+        }
+    }
+
+    let enabled = light_names
+        .iter()
+        .map(|name| {
+            let included = match &config.include {
+                Some(include) => name.as_ref().is_some_and(|n| include.contains(n)),
+                None => true,
+            };
+            let excluded = name.as_ref().is_some_and(|n| config.exclude.contains(n));
+            included && !excluded
+        })
+        .collect();
+
+    Ok(Arc::new(LightLinks::new(enabled)))
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let scene_path = env::args()
         .nth(1)
@@ -85,31 +585,203 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         scene.camera.focus_dist,
     ));
 
+    // ボリューメトリック効果の設定（フォグは解析的に、関与媒質はパストレーシングで扱う）
+    let mut volumetric: Option<Box<dyn VolumetricMedium>> = None;
+    let mut medium: Option<Arc<dyn Medium>> = None;
+    match scene.volumetric {
+        Some(VolumetricConfig::UniformFog { color, density }) => {
+            volumetric = Some(Box::new(UniformFog::new(color.into(), density)));
+        }
+        Some(VolumetricConfig::Heterogeneous {
+            bounds,
+            density,
+            absorption,
+            scattering,
+            phase,
+            emission,
+        }) => {
+            let absorption = absorption.map_or(ColorGlam::new(0.0, 0.0, 0.0), Into::into);
+            medium = Some(Arc::new(build_heterogeneous(
+                *density,
+                absorption,
+                scattering.into(),
+                bounds.map(|bounds| AABB::new(bounds.min.into(), bounds.max.into())),
+                phase,
+                emission,
+            )?));
+        }
+        None => {}
+    }
+
+    // 天空モデルの設定
+    let sky: Option<Arc<PreethamSky>> = scene.sky.map(|config| match config {
+        SkyConfig::Preetham {
+            sun_elevation,
+            sun_azimuth,
+            turbidity,
+            intensity,
+            sun_size,
+        } => Arc::new(PreethamSky::new(
+            sun_elevation,
+            sun_azimuth,
+            turbidity,
+            intensity,
+            sun_size,
+        )),
+    });
+
+    // 背景の設定（天空モデルがあればライティングに使用）
+    let environment: Arc<dyn Background> = match &sky {
+        Some(sky) => sky.clone(),
+        None => Arc::new(GradientBackground::default()),
+    };
+    let (background, backplate): (Arc<dyn Background>, Option<Backplate>) = match scene.background {
+        Some(BackgroundConfig::Solid { color }) => {
+            (Arc::new(SolidBackground::new(color.into())), None)
+        }
+        Some(BackgroundConfig::Gradient { bottom, top }) => (
+            Arc::new(GradientBackground::new(bottom.into(), top.into())),
+            None,
+        ),
+        Some(BackgroundConfig::Backplate { image }) => (
+            environment,
+            Some(Backplate::new(image::load_image(&image)?)),
+        ),
+        None => (environment, None),
+    };
+
+    // 光源の設定（天空モデルの太陽も光源として扱う）
+    let mut lights: Vec<Arc<dyn Light>> = Vec::new();
+    let mut light_names: Vec<Option<String>> = Vec::new();
+    if let Some(sky) = &sky {
+        lights.push(Arc::new(SunLight::new(sky.clone())));
+        light_names.push(Some("sun".to_string()));
+    }
+    for light in scene.lights {
+        lights.push(match light {
+            LightConfig::Point {
+                name,
+                position,
+                color,
+                intensity,
+                ies,
+                ies_rotation,
+            } => {
+                light_names.push(name);
+                let intensity = light_intensity(intensity, &ies)?;
+                let light = PointLight::new(position.into(), color.into(), intensity);
+                match ies {
+                    Some(path) => Arc::new(light.with_profile(Arc::new(
+                        IesProfile::from_file(&path)?.with_rotation(ies_rotation.unwrap_or(0.0)),
+                    ))),
+                    None => Arc::new(light),
+                }
+            }
+            LightConfig::Spot {
+                name,
+                position,
+                direction,
+                color,
+                intensity,
+                cone_angle,
+                falloff_start,
+                ies,
+                ies_rotation,
+            } => {
+                light_names.push(name);
+                let intensity = light_intensity(intensity, &ies)?;
+                let light = SpotLight::new(
+                    position.into(),
+                    direction.into(),
+                    color.into(),
+                    intensity,
+                    cone_angle,
+                    falloff_start.unwrap_or(cone_angle),
+                );
+                match ies {
+                    Some(path) => Arc::new(light.with_profile(Arc::new(
+                        IesProfile::from_file(&path)?.with_rotation(ies_rotation.unwrap_or(0.0)),
+                    ))),
+                    None => Arc::new(light),
+                }
+            }
+        });
+    }
+    // ライトリンクで光源を一意に指せるよう、名前の重複は許さない（sunは天空の太陽が使う）
+    let mut seen = std::collections::HashSet::new();
+    for name in light_names.iter().flatten() {
+        if !seen.insert(name) {
+            return Err(if sky.is_some() && name == "sun" {
+                "光源名 sun は天空モデルの太陽に予約されています".to_string()
+            } else {
+                format!("光源名が重複しています: {}", name)
+            }
+            .into());
+        }
+    }
+    let light_sampler: Box<dyn LightSampler> = match scene.light_sampler {
+        LightSamplerConfig::Uniform => Box::new(UniformLightSampler::new(lights.len())),
+        LightSamplerConfig::Bvh => Box::new(BvhLightSampler::new(&lights)),
+    };
+    println!(
+        "光源数: {} ({:?}でサンプリング)",
+        lights.len(),
+        scene.light_sampler
+    );
+    let lights = LightList::new(lights, light_sampler);
+
+    // 名前付きマテリアルの設定（ライブラリは後に指定したものほど、シーンの定義はそれらより優先）
+    let mut material_configs = HashMap::new();
+    for path in &scene.material_libraries {
+        material_configs.extend(MaterialLibraryConfig::from_yaml_file(path)?.materials);
+    }
+    material_configs.extend(scene.materials);
+    let mut materials = MaterialLibrary::new(material_configs);
+
+    // オブジェクトの設定
     let mut world = HittableList::new();
 
     for obj in scene.objects {
-        let material: Arc<dyn Material> = match obj.material {
-            MaterialConfig::Lambertian { albedo } => Arc::new(Lambertian::new(albedo.into())),
-            MaterialConfig::Metal { albedo, fuzz } => Arc::new(Metal::new(albedo.into(), fuzz)),
-            MaterialConfig::Dielectric { ir } => Arc::new(Dielectric::new(ir)),
+        // 媒質のみを指定した場合は、表面は見えない境界として扱う
+        let material: Arc<dyn Material> = match (obj.material, &obj.medium) {
+            (Some(material), _) => materials.resolve(material)?,
+            (None, Some(_)) => Arc::new(Interface),
+            (None, None) => {
+                return Err("オブジェクトには material か medium の指定が必要です".into());
+            }
         };
 
-        match obj.shape {
+        let object: Arc<dyn Hittable> = match obj.shape {
             ShapeConfig::Sphere { center, radius } => {
-                world.add(Arc::new(Sphere::new(center.into(), radius, material)));
+                Arc::new(Sphere::new(center.into(), radius, material))
             }
-        }
-    }
+        };
 
-    // ボリューメトリック効果の設定
-    let volumetric: Option<Arc<Box<dyn VolumetricMedium>>> =
-        scene.volumetric.map(|config| match config {
-            VolumetricConfig::UniformFog { color, density } => {
-                Arc::new(
-                    Box::new(UniformFog::new(color.into(), density)) as Box<dyn VolumetricMedium>
-                )
+        // 内部を媒質で満たす
+        let object: Arc<dyn Hittable> = match obj.medium {
+            Some(config) => {
+                let medium = build_medium(config, object.bounding_box(0.0, 0.0))?;
+                Arc::new(FilledObject::new(object, medium))
             }
-        });
+            None => object,
+        };
+
+        // 可視性やライトリンクの指定がある場合のみラップする
+        let visibility: Visibility = obj.visibility.into();
+        let light_links = obj
+            .light_links
+            .map(|links| resolve_light_links(&links, &light_names))
+            .transpose()?;
+        let object: Arc<dyn Hittable> = if light_links.is_some()
+            || !(visibility.camera && visibility.shadow && visibility.secondary)
+        {
+            Arc::new(LinkedObject::new(object, visibility, light_links))
+        } else {
+            object
+        };
+
+        world.add(object);
+    }
 
     // 画像の基本設定
     let aspect_ratio = 16.0 / 9.0;
@@ -119,10 +791,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // レンダリングの設定
     let max_depth = 50; // 反射の最大回数
+    let pixel_spread = scene.camera.vfov.to_radians() / image_height as f64; // 1ピクセルの見込み角
 
     // BVHを構築してシーンを最適化
     let world = world.optimize();
 
+    let integrator = Arc::new(
+        Integrator::new(
+            world, volumetric, sky, lights, background, backplate, max_depth,
+        )
+        .with_transparent_background(scene.output.alpha)
+        .with_medium(medium),
+    );
+
     // プログレス表示の設定
     let multi_progress = MultiProgress::new();
     let total_progress =
@@ -136,19 +817,20 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let completed_pixels = Arc::new(AtomicUsize::new(0));
 
-    // 画像の生成
-    let pixels: Vec<ColorGlam> = (0..image_height)
+    // 画像の生成（各ピクセルの推定値の分散も記録する）
+    let (samples, variances): (Vec<(ColorGlam, f64)>, Vec<f64>) = (0..image_height)
         .into_par_iter()
         .rev()
         .flat_map(|j| {
             let camera = Arc::clone(&camera);
-            let world = Arc::clone(&world);
-            let volumetric = volumetric.clone();
+            let integrator = Arc::clone(&integrator);
             let completed_pixels = Arc::clone(&completed_pixels);
             let total_progress = Arc::clone(&total_progress);
 
             (0..image_width).into_par_iter().map(move |i| {
                 let mut pixel_color = ColorGlam::new(0.0, 0.0, 0.0);
+                let mut alpha_sum = 0.0;
+                let mut luminance_squared_sum = 0.0;
                 let mut rng = rand::thread_rng();
 
                 // 各ピクセルに対して複数回サンプリング
@@ -156,14 +838,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     let u = (i as f64 + rng.gen_range(0.0..1.0)) / (image_width - 1) as f64;
                     let v = (j as f64 + rng.gen_range(0.0..1.0)) / (image_height - 1) as f64;
 
-                    let ray = camera.get_ray(u, v);
-                    pixel_color = pixel_color
-                        + ray_color(
-                            &ray,
-                            &world,
-                            volumetric.as_deref().map(|v| v.as_ref()),
-                            max_depth,
-                        );
+                    let ray = camera.get_ray(u, v).with_cone(0.0, pixel_spread);
+                    let (sample, alpha) = integrator.camera_ray_color(&ray, u, v);
+                    pixel_color = pixel_color + sample;
+                    alpha_sum += alpha;
+                    luminance_squared_sum += sample.luminance().powi(2);
                 }
 
                 // プログレスバーの更新
@@ -171,17 +850,34 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 total_progress.set_position(completed_pixels.load(Ordering::Relaxed) as u64);
 
                 // サンプリング結果の平均を取る
-                pixel_color * (1.0 / samples_per_pixel as f64)
+                let n = samples_per_pixel as f64;
+                let mean = pixel_color * (1.0 / n);
+                let sample_variance =
+                    (luminance_squared_sum / n - mean.luminance().powi(2)).max(0.0) * n / (n - 1.0);
+
+                ((mean, alpha_sum / n), sample_variance / n)
             })
         })
-        .collect();
+        .unzip();
 
     total_progress.finish_with_message("レンダリング完了");
 
+    // 光源サンプリング手法の比較用に推定値の分散の平均を表示
+    let mean_variance = variances.iter().sum::<f64>() / variances.len() as f64;
+    println!("\nピクセル輝度の推定分散（平均）: {:.6e}", mean_variance);
+
     println!("\nファイルに書き込んでいます...");
+    let (pixels, alphas): (Vec<ColorGlam>, Vec<f64>) = samples.into_iter().unzip();
     if let Err(e) = image::write_ppm("output.ppm", image_width, image_height, &pixels) {
         eprintln!("Error writing image: {}", e);
     }
+    // 合成用にアルファチャンネル付きの画像も出力
+    if scene.output.alpha
+        && let Err(e) =
+            image::write_png_rgba("output.png", image_width, image_height, &pixels, &alphas)
+    {
+        eprintln!("Error writing image: {}", e);
+    }
     println!("完了！");
 
     Ok(())
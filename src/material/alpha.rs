@@ -0,0 +1,83 @@
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::types::{HitRecord, Material, Matte, ScatterInfo};
+use crate::vec3_glam::Vec3Glam;
+use rand::Rng;
+use std::sync::Arc;
+
+type ColorGlam = Vec3Glam;
+
+/// アルファ値から衝突を有効とするかの判定方法
+#[derive(Debug, Clone, Copy)]
+pub enum AlphaMode {
+    /// アルファ値がしきい値以上の場合のみ有効
+    Threshold(f64),
+    /// アルファ値の確率で有効（半透明を確率的に表現する）
+    Stochastic,
+}
+
+/// 不透明度テクスチャで表面を切り抜くマテリアル
+///
+/// 切り抜かれた部分はカメラレイ・反射レイ・シャドウレイのいずれからも見えない
+pub struct AlphaMasked {
+    inner: Arc<dyn Material>,
+    /// 輝度を不透明度として使用するテクスチャ
+    opacity: Arc<dyn Texture>,
+    mode: AlphaMode,
+}
+
+impl AlphaMasked {
+    /// 不透明度テクスチャを適用したマテリアルを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - 切り抜かれなかった部分で使用するマテリアル
+    /// * `opacity` - 輝度を不透明度（0.0 ~ 1.0）として使用するテクスチャ
+    /// * `mode` - 衝突を有効とするかの判定方法
+    pub fn new(inner: Arc<dyn Material>, opacity: Arc<dyn Texture>, mode: AlphaMode) -> Self {
+        AlphaMasked {
+            inner,
+            opacity,
+            mode,
+        }
+    }
+}
+
+impl Material for AlphaMasked {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
+        self.inner.scatter(ray_in, rec)
+    }
+
+    fn eval(&self, ray_in: &Ray, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        self.inner.eval(ray_in, rec, direction)
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> ColorGlam {
+        self.inner.emitted(rec)
+    }
+
+    fn alpha_test(&self, rec: &HitRecord) -> bool {
+        let alpha = self.opacity.value_at(rec).luminance();
+        let visible = match self.mode {
+            AlphaMode::Threshold(threshold) => alpha >= threshold,
+            AlphaMode::Stochastic => alpha >= 1.0 || rand::thread_rng().gen_range(0.0..1.0) < alpha,
+        };
+        visible && self.inner.alpha_test(rec)
+    }
+
+    fn matte(&self, rec: &HitRecord) -> Option<Matte> {
+        self.inner.matte(rec)
+    }
+
+    fn is_interface(&self, rec: &HitRecord) -> bool {
+        self.inner.is_interface(rec)
+    }
+
+    fn is_thin(&self, rec: &HitRecord) -> bool {
+        self.inner.is_thin(rec)
+    }
+
+    fn shadow_transmittance(&self, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        self.inner.shadow_transmittance(rec, direction)
+    }
+}
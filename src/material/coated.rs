@@ -0,0 +1,176 @@
+use crate::ray::Ray;
+use crate::types::{HitRecord, Material, ScatterInfo};
+use crate::vec3_glam::Vec3Glam;
+use rand::Rng;
+use std::sync::Arc;
+
+use super::microfacet::{GgxDistribution, ShadingFrame, fresnel_dielectric};
+
+type ColorGlam = Vec3Glam;
+
+/// 任意のマテリアルの上に誘電体のクリアコート層を重ねたマテリアル
+///
+/// コート層の表面でフレネル反射率に従って反射し、透過した光は下地のマテリアルで散乱する。
+/// 層の内部での屈折と多重反射は無視し、出入りの透過率のみを考慮する
+pub struct Coated {
+    base: Arc<dyn Material>,
+    /// コート層の屈折率
+    ir: f64,
+    distribution: GgxDistribution,
+    /// コート層を垂直に1回通過したときの透過率（RGBごと）
+    tint: ColorGlam,
+}
+
+impl Coated {
+    /// クリアコートを重ねたマテリアルを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - コート層の下地となるマテリアル
+    /// * `ir` - コート層の屈折率
+    /// * `roughness` - コート層の表面の粗さ（0.0 ~ 1.0、0.0で滑らかな表面）
+    pub fn new(base: Arc<dyn Material>, ir: f64, roughness: f64) -> Self {
+        Coated {
+            base,
+            ir,
+            distribution: GgxDistribution::from_roughness(roughness),
+            tint: ColorGlam::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    /// コート層を色付きにする（ニスなど）
+    ///
+    /// # Arguments
+    ///
+    /// * `tint` - コート層を垂直に1回通過したときの透過率（RGBごと）
+    pub fn with_tint(mut self, tint: ColorGlam) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// 指定の角度でコート層を通過する際の色の吸収（屈折後の経路長に応じる）
+    fn absorption(&self, cos_theta: f64) -> ColorGlam {
+        let cos_theta = cos_theta.clamp(1e-4, 1.0);
+        let sin2_t = (1.0 - cos_theta * cos_theta) / (self.ir * self.ir);
+        let path = 1.0 / (1.0 - sin2_t).max(1e-4).sqrt();
+        ColorGlam::new(
+            self.tint.x().powf(path),
+            self.tint.y().powf(path),
+            self.tint.z().powf(path),
+        )
+    }
+
+    /// 指定の角度でコート層を出入りする際の透過率（フレネル透過率と吸収）
+    fn transmittance(&self, cos_theta: f64) -> ColorGlam {
+        self.absorption(cos_theta) * (1.0 - fresnel_dielectric(cos_theta, self.ir))
+    }
+}
+
+impl Material for Coated {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
+        // 物体の内側からはコート層を考慮しない
+        if !rec.front_face {
+            return self.base.scatter(ray_in, rec);
+        }
+
+        let frame = ShadingFrame::from_hit(rec);
+        let wo = frame.to_local(-1.0 * ray_in.direction().unit_vector());
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        let mut rng = rand::thread_rng();
+        let h = if self.distribution.is_smooth() {
+            Vec3Glam::new(0.0, 0.0, 1.0)
+        } else {
+            self.distribution.sample_visible_normal(
+                wo,
+                rng.gen_range(0.0..1.0),
+                rng.gen_range(0.0..1.0),
+            )
+        };
+        let wo_dot_h = wo.dot(&h);
+
+        // フレネル反射率の確率でコート層の表面で反射する
+        if fresnel_dielectric(wo_dot_h, self.ir) > rng.gen_range(0.0..1.0) {
+            let wi = h * (2.0 * wo_dot_h) - wo;
+            if wi.z() <= 0.0 {
+                return None;
+            }
+            let weight = if self.distribution.is_smooth() {
+                1.0
+            } else {
+                self.distribution.g2(wo, wi) / self.distribution.g1(wo)
+            };
+            // 粗いコート層の反射は直接光サンプリングでカバーされる
+            return Some(ScatterInfo {
+                scattered: Ray::new(rec.point, frame.to_world(wi)),
+                attenuation: ColorGlam::new(weight, weight, weight),
+                specular: self.distribution.is_smooth(),
+            });
+        }
+
+        // コート層を透過して下地で散乱し、再びコート層を通って出ていく
+        let scatter = self.base.scatter(ray_in, rec)?;
+        let wi = frame.to_local(scatter.scattered.direction().unit_vector());
+        if wi.z() <= 0.0 {
+            // 下地が透過した場合はコート層の出口を考慮しない
+            return Some(scatter);
+        }
+        // 入射側のフレネル透過率は反射しなかった確率で相殺される
+        Some(ScatterInfo {
+            attenuation: scatter.attenuation * self.absorption(wo.z()) * self.transmittance(wi.z()),
+            ..scatter
+        })
+    }
+
+    fn eval(&self, ray_in: &Ray, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        let base = self.base.eval(ray_in, rec, direction);
+        if !rec.front_face {
+            return base;
+        }
+
+        let frame = ShadingFrame::from_hit(rec);
+        let wo = frame.to_local(-1.0 * ray_in.direction().unit_vector());
+        let wi = frame.to_local(direction);
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return base;
+        }
+
+        // 下地の寄与はコート層を出入りする透過率で減衰する
+        let base = base.map(|base| base * self.transmittance(wo.z()) * self.transmittance(wi.z()));
+
+        // 粗いコート層の反射 f・cos = D・G2・F / (4・cosθo)（滑らかな場合は散乱レイで扱う）
+        if self.distribution.is_smooth() {
+            return base;
+        }
+        let h = (wo + wi).unit_vector();
+        let coat = fresnel_dielectric(wo.dot(&h), self.ir)
+            * self.distribution.d(h)
+            * self.distribution.g2(wo, wi)
+            / (4.0 * wo.z());
+        Some(base.unwrap_or(ColorGlam::new(0.0, 0.0, 0.0)) + ColorGlam::new(coat, coat, coat))
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> ColorGlam {
+        self.base.emitted(rec)
+    }
+
+    fn alpha_test(&self, rec: &HitRecord) -> bool {
+        self.base.alpha_test(rec)
+    }
+
+    fn is_thin(&self, rec: &HitRecord) -> bool {
+        self.base.is_thin(rec)
+    }
+
+    fn shadow_transmittance(&self, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        // 表側ではコート層を1回通過する
+        let transmittance = self.base.shadow_transmittance(rec, direction)?;
+        if !rec.front_face {
+            return Some(transmittance);
+        }
+        let cos_theta = direction.unit_vector().dot(&rec.normal).abs();
+        Some(transmittance * self.transmittance(cos_theta))
+    }
+}
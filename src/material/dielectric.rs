@@ -1,56 +1,383 @@
 use crate::ray::Ray;
+use crate::spectrum::{SampledWavelengths, WAVELENGTH_SAMPLES};
 use crate::types::{HitRecord, Material, ScatterInfo};
 use crate::vec3_glam::Vec3Glam;
+use crate::volumetric::calculate_beer_lambert_attenuation;
 use rand::Rng;
 
+use super::microfacet::{GgxDistribution, ShadingFrame, fresnel_dielectric};
+use super::thin_film::ThinFilm;
+
 type ColorGlam = Vec3Glam;
 
+/// 屈折率の波長依存性（分散）のモデル
+#[derive(Debug, Clone, Copy)]
+pub enum Dispersion {
+    /// Cauchyの式 n = A + B / λ²（λはµm）
+    Cauchy { a: f64, b: f64 },
+    /// Sellmeierの式 n² = 1 + Σ Bᵢλ² / (λ² - Cᵢ)（λはµm、Cᵢはµm²）
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    /// 光学ガラスBK7
+    pub const BK7: Dispersion = Dispersion::Sellmeier {
+        b: [1.03961212, 0.231792344, 1.01046945],
+        c: [0.00600069867, 0.0200179144, 103.560653],
+    };
+
+    /// ダイヤモンド
+    pub const DIAMOND: Dispersion = Dispersion::Sellmeier {
+        b: [0.3306, 4.3356, 0.0],
+        c: [0.030625, 0.011236, 0.0],
+    };
+
+    /// 指定波長（nm）での屈折率
+    pub fn ior(&self, wavelength: f64) -> f64 {
+        let lambda2 = (wavelength * 1e-3).powi(2);
+        match self {
+            Dispersion::Cauchy { a, b } => a + b / lambda2,
+            Dispersion::Sellmeier { b, c } => (1.0
+                + b.iter()
+                    .zip(c)
+                    .map(|(b, c)| b * lambda2 / (lambda2 - c))
+                    .sum::<f64>())
+            .sqrt(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Dielectric {
     // 屈折率（Index of Refraction）
     ir: f64,
+    // 表面の粗さ（すりガラス用）
+    distribution: GgxDistribution,
+    // 内部を単位長さ進むごとの吸収係数（RGBごと）
+    absorption: ColorGlam,
+    // 分散（Noneの場合は波長によらずirを使用）
+    dispersion: Option<Dispersion>,
+    // 表面を覆う薄膜
+    thin_film: Option<ThinFilm>,
 }
 
 impl Dielectric {
-    pub fn new(ir: f64) -> Self {
-        Dielectric { ir }
+    /// 新しい誘電体マテリアルを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `ir` - 屈折率
+    /// * `roughness` - 表面の粗さ（0.0 ~ 1.0、0.0で滑らかな表面）
+    pub fn new(ir: f64, roughness: f64) -> Self {
+        Dielectric {
+            ir,
+            distribution: GgxDistribution::from_roughness(roughness),
+            absorption: ColorGlam::new(0.0, 0.0, 0.0),
+            dispersion: None,
+            thin_film: None,
+        }
     }
 
-    // Schlickの近似を用いた反射率の計算
-    fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
-        let r0 = ((1.0 - ref_idx) / (1.0 + ref_idx)).powi(2);
-        r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
+    /// 表面を薄膜で覆った誘電体を作成（コーティングされたレンズなど）
+    pub fn with_thin_film(mut self, thin_film: ThinFilm) -> Self {
+        self.thin_film = Some(thin_film);
+        self
     }
-}
 
-impl Material for Dielectric {
-    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
-        let mut rng = rand::thread_rng();
+    /// 分散を設定した誘電体を作成（プリズムや宝石用）
+    ///
+    /// 分散のある誘電体に当たったパスはヒーロー波長と随伴波長を選んで以降はそれらを追跡し、
+    /// 屈折した時点でヒーロー波長のみに絞り込む
+    pub fn with_dispersion(mut self, dispersion: Dispersion) -> Self {
+        self.dispersion = Some(dispersion);
+        self
+    }
 
-        let refraction_ratio = if rec.front_face {
-            1.0 / self.ir
-        } else {
-            self.ir
-        };
+    /// 内部での吸収を設定した誘電体を作成（色付きガラス用）
+    ///
+    /// # Arguments
+    ///
+    /// * `absorption` - 単位長さあたりの吸収係数（RGBごと）
+    pub fn with_absorption(mut self, absorption: ColorGlam) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// 指定の深さで指定の透過率となる吸収係数を計算
+    ///
+    /// # Arguments
+    ///
+    /// * `transmittance` - 透過率（RGBごと、0.0より大きく1.0以下）
+    /// * `depth` - 透過率を指定する基準の深さ
+    pub fn absorption_from_transmittance(transmittance: ColorGlam, depth: f64) -> ColorGlam {
+        let coefficient = |t: f64| -t.clamp(1e-6, 1.0).ln() / depth;
+        ColorGlam::new(
+            coefficient(transmittance.x()),
+            coefficient(transmittance.y()),
+            coefficient(transmittance.z()),
+        )
+    }
+
+    /// 内部を通過した距離に応じた透過率
+    fn interior_transmittance(&self, ray_in: &Ray, rec: &HitRecord) -> ColorGlam {
+        // 裏面に当たった場合、レイは始点から衝突点まで内部を進んできた
+        if rec.front_face {
+            return ColorGlam::new(1.0, 1.0, 1.0);
+        }
+        let distance = rec.t * ray_in.direction().length();
+        ColorGlam::new(
+            calculate_beer_lambert_attenuation(self.absorption.x(), distance),
+            calculate_beer_lambert_attenuation(self.absorption.y(), distance),
+            calculate_beer_lambert_attenuation(self.absorption.z(), distance),
+        )
+    }
+
+    /// 入射側に対する透過側の相対屈折率
+    fn relative_ior(ir: f64, rec: &HitRecord) -> f64 {
+        if rec.front_face { ir } else { 1.0 / ir }
+    }
+
+    /// RGBのパスでの反射率（表側から入射した場合は薄膜を考慮する）
+    fn reflectance(&self, cos_theta: f64, rec: &HitRecord) -> ColorGlam {
+        match self.thin_film.filter(|_| rec.front_face) {
+            Some(thin_film) => thin_film.reflectance(cos_theta, |_| (self.ir, 0.0)),
+            None => {
+                let r = fresnel_dielectric(cos_theta, Self::relative_ior(self.ir, rec));
+                ColorGlam::new(r, r, r)
+            }
+        }
+    }
+
+    /// 波長の組を追跡するパスでの波長ごとの反射率（全反射では1.0）
+    fn spectral_reflectance(
+        &self,
+        cos_theta: f64,
+        rec: &HitRecord,
+        wavelengths: &SampledWavelengths,
+    ) -> [f64; WAVELENGTH_SAMPLES] {
+        let thin_film = self.thin_film.filter(|_| rec.front_face);
+        wavelengths.map(|wavelength| {
+            let ir = self.ior(Some(wavelength));
+            match thin_film {
+                Some(thin_film) => thin_film.reflectance_at(cos_theta, (ir, 0.0), wavelength),
+                None => fresnel_dielectric(cos_theta, Self::relative_ior(ir, rec)),
+            }
+        })
+    }
+
+    /// 指定波長での屈折率（分散がない場合や波長が決まっていない場合はir）
+    fn ior(&self, wavelength: Option<f64>) -> f64 {
+        match (self.dispersion, wavelength) {
+            (Some(dispersion), Some(wavelength)) => dispersion.ior(wavelength),
+            _ => self.ir,
+        }
+    }
+
+    /// 反射率に従って反射と透過のどちらを選ぶかを決める
+    ///
+    /// RGBのパスではRGBの反射率を使い、波長の組を追跡するパスでは
+    /// 波長ごとの屈折率での反射率から各波長の重みに係数を掛ける。
+    /// 戻り値は反射したかどうか、RGBの重み、係数を掛けた波長の組
+    ///
+    /// # Arguments
+    ///
+    /// * `cos_theta` - 入射角の余弦（粗い表面ではマイクロファセット法線に対する値）
+    /// * `rec` - 衝突点の情報
+    /// * `wavelengths` - パスが追跡する波長の組
+    fn choose(
+        &self,
+        cos_theta: f64,
+        rec: &HitRecord,
+        wavelengths: Option<SampledWavelengths>,
+    ) -> (bool, ColorGlam, Option<SampledWavelengths>) {
+        match wavelengths {
+            None => {
+                let (reflect, weight) = choose_reflection(self.reflectance(cos_theta, rec));
+                (reflect, weight, None)
+            }
+            Some(wavelengths) => {
+                let reflectance = self.spectral_reflectance(cos_theta, rec, &wavelengths);
+                let (reflect, factors) = choose_reflection_spectral(reflectance);
+                (
+                    reflect,
+                    ColorGlam::new(1.0, 1.0, 1.0),
+                    Some(wavelengths.scaled(factors)),
+                )
+            }
+        }
+    }
 
+    /// 屈折した後に追跡する波長の組
+    ///
+    /// 分散がある場合は屈折の方向がヒーロー波長で決まるため、随伴波長を打ち切る
+    fn refracted_wavelengths(
+        &self,
+        wavelengths: Option<SampledWavelengths>,
+    ) -> Option<SampledWavelengths> {
+        match self.dispersion {
+            Some(_) => wavelengths.map(SampledWavelengths::terminate_secondary),
+            None => wavelengths,
+        }
+    }
+
+    /// 滑らかな境界面での反射または屈折
+    ///
+    /// 戻り値は散乱方向、重み、散乱後に追跡する波長の組
+    fn scatter_smooth(
+        &self,
+        ray_in: &Ray,
+        rec: &HitRecord,
+        wavelengths: Option<SampledWavelengths>,
+    ) -> Option<(Vec3Glam, ColorGlam, Option<SampledWavelengths>)> {
         let unit_direction = ray_in.direction().unit_vector();
         let cos_theta = (-unit_direction).dot(&rec.normal).min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
-        let cannot_refract = refraction_ratio * sin_theta > 1.0;
-        let direction = if cannot_refract
-            || Self::reflectance(cos_theta, refraction_ratio) > rng.gen_range(0.0..1.0)
-        {
-            // 全反射または確率的な反射
-            unit_direction.reflect(&rec.normal)
+        let (reflect, weight, wavelengths) = self.choose(cos_theta, rec, wavelengths);
+        if reflect {
+            return Some((unit_direction.reflect(&rec.normal), weight, wavelengths));
+        }
+
+        // 屈折の方向はヒーロー波長の屈折率で決まる（全反射する場合は寄与がない）
+        let ratio = 1.0 / Self::relative_ior(self.ior(wavelengths.map(|w| w.hero())), rec);
+        if ratio * sin_theta > 1.0 {
+            return None;
+        }
+        Some((
+            unit_direction.refract(&rec.normal, ratio),
+            weight,
+            self.refracted_wavelengths(wavelengths),
+        ))
+    }
+
+    /// GGX分布のマイクロファセットでの反射または屈折
+    ///
+    /// 可視法線をサンプリングし、フレネル反射率に従って反射と屈折を選ぶ。
+    /// 戻り値は散乱方向、重み（G2 / G1）、散乱後に追跡する波長の組
+    fn scatter_rough(
+        &self,
+        ray_in: &Ray,
+        rec: &HitRecord,
+        wavelengths: Option<SampledWavelengths>,
+    ) -> Option<(Vec3Glam, ColorGlam, Option<SampledWavelengths>)> {
+        let mut rng = rand::thread_rng();
+
+        let frame = ShadingFrame::from_hit(rec);
+        let wo = frame.to_local(-1.0 * ray_in.direction().unit_vector());
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        let h = self.distribution.sample_visible_normal(
+            wo,
+            rng.gen_range(0.0..1.0),
+            rng.gen_range(0.0..1.0),
+        );
+        let wo_dot_h = wo.dot(&h);
+
+        let (reflect, fresnel_weight, wavelengths) = self.choose(wo_dot_h, rec, wavelengths);
+        let (wi, wavelengths) = if reflect {
+            // マイクロファセットでの反射（表面の裏側へ向かう場合は吸収）
+            let wi = h * (2.0 * wo_dot_h) - wo;
+            if wi.z() <= 0.0 {
+                return None;
+            }
+            (wi, wavelengths)
+        } else {
+            // マイクロファセットでの屈折（ヒーロー波長が全反射する場合や、
+            // 表面の表側へ向かう場合は吸収）
+            let eta = Self::relative_ior(self.ior(wavelengths.map(|w| w.hero())), rec);
+            if fresnel_dielectric(wo_dot_h, eta) >= 1.0 {
+                return None;
+            }
+            let wi = (-1.0 * wo).refract(&h, 1.0 / eta);
+            if wi.z() >= 0.0 {
+                return None;
+            }
+            (wi, self.refracted_wavelengths(wavelengths))
+        };
+
+        let weight = self.distribution.g2(wo, wi) / self.distribution.g1(wo);
+        Some((frame.to_world(wi), fresnel_weight * weight, wavelengths))
+    }
+}
+
+impl Material for Dielectric {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
+        // 分散がある場合はパスが追跡する波長の組を決め、波長ごとの屈折率を使用する
+        let wavelengths = match (self.dispersion, ray_in.wavelengths()) {
+            (Some(_), None) => Some(SampledWavelengths::sample(
+                rand::thread_rng().gen_range(0.0..1.0),
+            )),
+            (_, wavelengths) => wavelengths.copied(),
+        };
+
+        let (direction, weight, wavelengths) = if self.distribution.is_smooth() {
+            self.scatter_smooth(ray_in, rec, wavelengths)?
         } else {
-            // 屈折
-            unit_direction.refract(&rec.normal, refraction_ratio)
+            self.scatter_rough(ray_in, rec, wavelengths)?
         };
 
+        // 粗い表面での反射は直接光サンプリングでカバーされる。
+        // 透過方向の直接光サンプリングは遮蔽されるため、屈折は散乱レイで光源を扱う
+        let reflected = direction.dot(&rec.normal) > 0.0;
         Some(ScatterInfo {
-            scattered: Ray::new(rec.point, direction),
-            attenuation: ColorGlam::new(1.0, 1.0, 1.0),
+            scattered: Ray::new(rec.point, direction).with_wavelengths(wavelengths),
+            attenuation: self.interior_transmittance(ray_in, rec) * weight,
+            specular: self.distribution.is_smooth() || !reflected,
         })
     }
+
+    fn eval(&self, ray_in: &Ray, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        if self.distribution.is_smooth() {
+            return None;
+        }
+
+        // 粗い表面での反射のローブのみを評価（透過は散乱レイで扱う）
+        let frame = ShadingFrame::from_hit(rec);
+        let wo = frame.to_local(-1.0 * ray_in.direction().unit_vector());
+        let wi = frame.to_local(direction);
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return Some(ColorGlam::new(0.0, 0.0, 0.0));
+        }
+
+        // f・cos = D・G2・F / (4・cosθo)
+        let h = (wo + wi).unit_vector();
+        let d = self.distribution.d(h);
+        let g = self.distribution.g2(wo, wi);
+        let fresnel = match ray_in.wavelengths() {
+            Some(wavelengths) => {
+                wavelengths.project(self.spectral_reflectance(wo.dot(&h), rec, wavelengths))
+            }
+            None => self.reflectance(wo.dot(&h), rec),
+        };
+        Some(self.interior_transmittance(ray_in, rec) * fresnel * (d * g / (4.0 * wo.z())))
+    }
+}
+
+/// RGBの反射率に従って反射と透過のどちらを選ぶかを決める
+///
+/// 反射率の平均を反射の確率とし、選んだ側の重み（反射率または透過率 / 確率）を返す
+pub(super) fn choose_reflection(reflectance: ColorGlam) -> (bool, ColorGlam) {
+    let probability = ((reflectance.x() + reflectance.y() + reflectance.z()) / 3.0).clamp(0.0, 1.0);
+    if rand::thread_rng().gen_range(0.0..1.0) < probability {
+        (true, reflectance / probability)
+    } else {
+        let transmittance = ColorGlam::new(1.0, 1.0, 1.0) - reflectance;
+        (false, transmittance / (1.0 - probability))
+    }
+}
+
+/// 波長ごとの反射率に従って反射と透過のどちらを選ぶかを決める
+///
+/// 反射率の平均を反射の確率とし、選んだ側の各波長の係数（反射率または透過率 / 確率）を返す
+pub(super) fn choose_reflection_spectral(
+    reflectance: [f64; WAVELENGTH_SAMPLES],
+) -> (bool, [f64; WAVELENGTH_SAMPLES]) {
+    let probability = (reflectance.iter().sum::<f64>() / WAVELENGTH_SAMPLES as f64).clamp(0.0, 1.0);
+    if rand::thread_rng().gen_range(0.0..1.0) < probability {
+        (true, reflectance.map(|r| r / probability))
+    } else {
+        (false, reflectance.map(|r| (1.0 - r) / (1.0 - probability)))
+    }
 }
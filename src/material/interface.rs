@@ -0,0 +1,28 @@
+use crate::ray::Ray;
+use crate::types::{HitRecord, Material, ScatterInfo};
+use crate::vec3_glam::Vec3Glam;
+
+type ColorGlam = Vec3Glam;
+
+/// 媒質の境界を示すだけの見えない表面（煙や雲などのボリュームの外形）
+///
+/// レイは向きを変えずに通過し、境界を越えた先の媒質はインテグレータが切り替える
+pub struct Interface;
+
+impl Material for Interface {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
+        Some(ScatterInfo {
+            scattered: Ray::new(rec.point, ray_in.direction()),
+            attenuation: ColorGlam::new(1.0, 1.0, 1.0),
+            specular: true,
+        })
+    }
+
+    fn is_interface(&self, _rec: &HitRecord) -> bool {
+        true
+    }
+
+    fn shadow_transmittance(&self, _rec: &HitRecord, _direction: Vec3Glam) -> Option<ColorGlam> {
+        Some(ColorGlam::new(1.0, 1.0, 1.0))
+    }
+}
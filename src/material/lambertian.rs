@@ -1,16 +1,19 @@
 use crate::ray::Ray;
+use crate::texture::Texture;
 use crate::types::{HitRecord, Material, ScatterInfo, random_unit_vector};
 use crate::vec3_glam::Vec3Glam;
+use std::f64::consts::PI;
+use std::sync::Arc;
 
 type ColorGlam = Vec3Glam;
 
 #[derive(Clone)]
 pub struct Lambertian {
-    albedo: ColorGlam,
+    albedo: Arc<dyn Texture>,
 }
 
 impl Lambertian {
-    pub fn new(albedo: ColorGlam) -> Self {
+    pub fn new(albedo: Arc<dyn Texture>) -> Self {
         Lambertian { albedo }
     }
 }
@@ -26,7 +29,14 @@ impl Material for Lambertian {
 
         Some(ScatterInfo {
             scattered: Ray::new(rec.point, scatter_direction),
-            attenuation: self.albedo,
+            attenuation: self.albedo.value_at(rec),
+            specular: false,
         })
     }
+
+    fn eval(&self, _ray_in: &Ray, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        // 拡散反射のBRDF（albedo / π）にコサイン項を掛ける
+        let cosine = rec.normal.dot(&direction).max(0.0);
+        Some(self.albedo.value_at(rec) * (cosine / PI))
+    }
 }
@@ -0,0 +1,68 @@
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::types::{HitRecord, Material, Matte, ScatterInfo};
+use crate::vec3_glam::Vec3Glam;
+use std::sync::Arc;
+
+use super::lambertian::Lambertian;
+
+type ColorGlam = Vec3Glam;
+
+/// 合成用に画面上のアルファを0にして背後を切り抜くマテリアル
+///
+/// カメラ以外のレイに対しては光を吸収する黒い物体として振る舞う
+pub struct Holdout;
+
+impl Material for Holdout {
+    fn scatter(&self, _ray_in: &Ray, _rec: &HitRecord) -> Option<ScatterInfo> {
+        None
+    }
+
+    fn eval(&self, _ray_in: &Ray, _rec: &HitRecord, _direction: Vec3Glam) -> Option<ColorGlam> {
+        Some(ColorGlam::new(0.0, 0.0, 0.0))
+    }
+
+    fn matte(&self, _rec: &HitRecord) -> Option<Matte> {
+        Some(Matte::Holdout)
+    }
+}
+
+/// 実写の背景画像に合成するための影受けマテリアル
+///
+/// カメラから見た場合は表面自体は描画せず、落ちた影と映り込みのみをアルファに記録する。
+/// カメラ以外のレイに対しては拡散反射面として振る舞い、周囲の物体に照り返しを与える
+pub struct ShadowCatcher {
+    surface: Lambertian,
+    reflection: f64,
+}
+
+impl ShadowCatcher {
+    /// 影受けマテリアルを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `albedo` - 周囲の物体への照り返しに使用する拡散反射色
+    /// * `reflection` - カメラから見た映り込みの強さ（0.0 ~ 1.0）
+    pub fn new(albedo: Arc<dyn Texture>, reflection: f64) -> Self {
+        ShadowCatcher {
+            surface: Lambertian::new(albedo),
+            reflection: reflection.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl Material for ShadowCatcher {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
+        self.surface.scatter(ray_in, rec)
+    }
+
+    fn eval(&self, ray_in: &Ray, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        self.surface.eval(ray_in, rec, direction)
+    }
+
+    fn matte(&self, _rec: &HitRecord) -> Option<Matte> {
+        Some(Matte::ShadowCatcher {
+            reflection: self.reflection,
+        })
+    }
+}
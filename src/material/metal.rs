@@ -1,87 +1,278 @@
 use crate::ray::Ray;
-use crate::types::{HitRecord, Material, ScatterInfo, random_unit_vector};
+use crate::spectrum::{SampledWavelengths, WAVELENGTH_SAMPLES};
+use crate::texture::Texture;
+use crate::types::{HitRecord, Material, ScatterInfo};
 use crate::vec3_glam::Vec3Glam;
+use rand::Rng;
+use std::sync::Arc;
+
+use super::microfacet::{GgxDistribution, ShadingFrame, fresnel_conductor};
+use super::thin_film::ThinFilm;
 
 type ColorGlam = Vec3Glam;
 
+/// 金属のフレネル反射率の表現
+#[derive(Clone)]
+pub enum ConductorFresnel {
+    /// 垂直入射での反射色を指定するSchlickの近似
+    Schlick(Arc<dyn Texture>),
+    /// RGBごとの複素屈折率（n + ik）
+    Complex { eta: ColorGlam, k: ColorGlam },
+}
+
+/// 代表的な金属の複素屈折率
+#[derive(Debug, Clone, Copy)]
+pub enum MetalPreset {
+    Gold,
+    Copper,
+    Aluminium,
+    Silver,
+}
+
+impl MetalPreset {
+    /// RGB（おおよそ650nm, 550nm, 450nm）での屈折率と消衰係数
+    pub fn ior(self) -> (ColorGlam, ColorGlam) {
+        let (eta, k) = match self {
+            MetalPreset::Gold => ((0.143, 0.374, 1.442), (3.983, 2.385, 1.603)),
+            MetalPreset::Copper => ((0.200, 0.924, 1.102), (3.912, 2.452, 2.142)),
+            MetalPreset::Aluminium => ((1.657, 0.880, 0.521), (9.224, 6.270, 4.837)),
+            MetalPreset::Silver => ((0.155, 0.117, 0.138), (4.828, 3.122, 2.147)),
+        };
+        (
+            ColorGlam::new(eta.0, eta.1, eta.2),
+            ColorGlam::new(k.0, k.1, k.2),
+        )
+    }
+}
+
+/// GGX分布のマイクロファセットモデルによる金属（導体）マテリアル
 #[derive(Clone)]
 pub struct Metal {
-    base_color: ColorGlam, // 金属の基本色
-    reflectivity: f64,     // 反射率（0.0 ~ 1.0）
-    metallicness: f64,     // 金属性（0.0 ~ 1.0）
-    roughness: f64,        // 表面の粗さ（0.0 ~ 1.0）
+    fresnel: ConductorFresnel,
+    distribution: GgxDistribution,
+    /// 異方性の向き（接線からの回転角、ラジアン）
+    rotation: f64,
+    /// 表面を覆う酸化膜など
+    thin_film: Option<ThinFilm>,
 }
 
 impl Metal {
-    /// 新しいメタルマテリアルを作成
+    /// 反射色を指定してメタルマテリアルを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `base_color` - 垂直入射での反射色
+    /// * `roughness` - 表面の粗さ（0.0 ~ 1.0）
+    pub fn new(base_color: Arc<dyn Texture>, roughness: f64) -> Self {
+        Self::with_fresnel(ConductorFresnel::Schlick(base_color), roughness)
+    }
+
+    /// 代表的な金属の複素屈折率を使用してメタルマテリアルを作成
     ///
     /// # Arguments
     ///
-    /// * `base_color` - 金属の基本色
+    /// * `preset` - 金属の種類
     /// * `roughness` - 表面の粗さ（0.0 ~ 1.0）
-    /// * `reflectivity` - 反射率（0.0 ~ 1.0、デフォルト0.9）
-    /// * `metallicness` - 金属性（0.0 ~ 1.0、デフォルト1.0）
-    pub fn new(base_color: ColorGlam, roughness: f64) -> Self {
-        Self::with_params(base_color, roughness, 0.9, 1.0)
+    pub fn from_preset(preset: MetalPreset, roughness: f64) -> Self {
+        let (eta, k) = preset.ior();
+        Self::with_fresnel(ConductorFresnel::Complex { eta, k }, roughness)
     }
 
-    /// すべてのパラメータを指定してメタルマテリアルを作成
-    pub fn with_params(
-        base_color: ColorGlam,
-        roughness: f64,
-        reflectivity: f64,
-        metallicness: f64,
-    ) -> Self {
+    /// フレネル反射率の表現を指定してメタルマテリアルを作成
+    pub fn with_fresnel(fresnel: ConductorFresnel, roughness: f64) -> Self {
         Metal {
-            base_color,
-            reflectivity: reflectivity.clamp(0.0, 1.0),
-            metallicness: metallicness.clamp(0.0, 1.0),
-            roughness: roughness.clamp(0.0, 1.0),
+            fresnel,
+            distribution: GgxDistribution::from_roughness(roughness),
+            rotation: 0.0,
+            thin_film: None,
         }
     }
 
-    /// フレネル反射率の計算（Schlickの近似を使用）
-    fn fresnel(&self, cos_theta: f64) -> f64 {
-        let r0 = self.reflectivity;
-        r0 + (1.0 - r0) * (1.0 - cos_theta).powi(5)
+    /// 表面を薄膜で覆った金属にする（焼けたチタンやステンレスの酸化膜など）
+    pub fn with_thin_film(mut self, thin_film: ThinFilm) -> Self {
+        self.thin_film = Some(thin_film);
+        self
+    }
+
+    /// 異方性のある金属にする（ヘアライン加工など）
+    ///
+    /// # Arguments
+    ///
+    /// * `anisotropy` - 異方性の強さ（-1.0 ~ 1.0、正で接線方向に伸びたハイライト）
+    /// * `rotation` - 接線からの異方性の向きの回転角（ラジアン）
+    pub fn with_anisotropy(mut self, anisotropy: f64, rotation: f64) -> Self {
+        self.distribution = self.distribution.with_anisotropy(anisotropy);
+        self.rotation = rotation;
+        self
     }
 
-    /// 金属/非金属の混合色を計算
-    fn mix_color(&self, pure_metal: ColorGlam) -> ColorGlam {
-        let dielectric = ColorGlam::new(0.04, 0.04, 0.04); // 非金属の反射色
-        dielectric * (1.0 - self.metallicness) + pure_metal * self.metallicness
+    /// 異方性の向きに合わせた局所座標系
+    fn frame(&self, rec: &HitRecord) -> ShadingFrame {
+        ShadingFrame::from_hit(rec).rotated(self.rotation)
+    }
+
+    /// マイクロファセットへの入射角に対するRGBのフレネル反射率
+    fn fresnel(&self, cos_theta: f64, rec: &HitRecord) -> ColorGlam {
+        if let Some(thin_film) = &self.thin_film {
+            let (eta, k) = self.complex_ior(rec);
+            return thin_film.reflectance(cos_theta, |wavelength| {
+                (
+                    interpolate_channels(eta, wavelength),
+                    interpolate_channels(k, wavelength),
+                )
+            });
+        }
+
+        match &self.fresnel {
+            ConductorFresnel::Schlick(base_color) => {
+                let f0 = base_color.value_at(rec);
+                let weight = (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5);
+                f0 + (ColorGlam::new(1.0, 1.0, 1.0) - f0) * weight
+            }
+            ConductorFresnel::Complex { eta, k } => ColorGlam::new(
+                fresnel_conductor(cos_theta, eta.x(), k.x()),
+                fresnel_conductor(cos_theta, eta.y(), k.y()),
+                fresnel_conductor(cos_theta, eta.z(), k.z()),
+            ),
+        }
+    }
+
+    /// 薄膜があり、パスが波長の組を追跡している場合の波長ごとの反射率
+    fn spectral_fresnel(
+        &self,
+        cos_theta: f64,
+        rec: &HitRecord,
+        wavelengths: Option<&SampledWavelengths>,
+    ) -> Option<[f64; WAVELENGTH_SAMPLES]> {
+        let (thin_film, wavelengths) = (self.thin_film.as_ref()?, wavelengths?);
+        let (eta, k) = self.complex_ior(rec);
+        Some(wavelengths.map(|wavelength| {
+            let substrate = (
+                interpolate_channels(eta, wavelength),
+                interpolate_channels(k, wavelength),
+            );
+            thin_film.reflectance_at(cos_theta, substrate, wavelength)
+        }))
+    }
+
+    /// 反射の重み（波長ごとの反射率がある場合は波長の重みに掛ける）
+    ///
+    /// 戻り値はRGBの重みと、散乱後に追跡する波長の組
+    fn reflection_weight(
+        &self,
+        cos_theta: f64,
+        ray_in: &Ray,
+        rec: &HitRecord,
+    ) -> (ColorGlam, Option<SampledWavelengths>) {
+        match self.spectral_fresnel(cos_theta, rec, ray_in.wavelengths()) {
+            Some(reflectance) => (
+                ColorGlam::new(1.0, 1.0, 1.0),
+                ray_in.wavelengths().map(|w| w.scaled(reflectance)),
+            ),
+            None => (self.fresnel(cos_theta, rec), None),
+        }
+    }
+
+    /// RGBごとの複素屈折率（Schlickの近似の場合は反射色から逆算した実屈折率）
+    fn complex_ior(&self, rec: &HitRecord) -> (ColorGlam, ColorGlam) {
+        match &self.fresnel {
+            ConductorFresnel::Schlick(base_color) => {
+                let f0 = base_color.value_at(rec);
+                let eta = |r: f64| {
+                    let s = r.clamp(0.0, 0.99).sqrt();
+                    (1.0 + s) / (1.0 - s)
+                };
+                (
+                    ColorGlam::new(eta(f0.x()), eta(f0.y()), eta(f0.z())),
+                    ColorGlam::new(0.0, 0.0, 0.0),
+                )
+            }
+            ConductorFresnel::Complex { eta, k } => (*eta, *k),
+        }
     }
 }
 
 impl Material for Metal {
     fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
-        let unit_direction = ray_in.direction().unit_vector();
+        let frame = self.frame(rec);
+        let wo = frame.to_local(-1.0 * ray_in.direction().unit_vector());
+        if wo.z() <= 0.0 {
+            return None;
+        }
 
-        // フレネル反射率の計算
-        let cos_theta = (-unit_direction).dot(&rec.normal).min(1.0);
-        let reflection_probability = self.fresnel(cos_theta);
+        // 滑らかな表面は完全鏡面反射として扱う
+        if self.distribution.is_smooth() {
+            let wi = Vec3Glam::new(-wo.x(), -wo.y(), wo.z());
+            let (attenuation, wavelengths) = self.reflection_weight(wo.z(), ray_in, rec);
+            return Some(ScatterInfo {
+                scattered: Ray::new(rec.point, frame.to_world(wi)).with_wavelengths(wavelengths),
+                attenuation,
+                specular: true,
+            });
+        }
 
-        // 完全鏡面反射方向の計算
-        let reflected = unit_direction.reflect(&rec.normal);
+        // 可視法線をサンプリングし、そのマイクロファセットで反射させる
+        let mut rng = rand::thread_rng();
+        let h = self.distribution.sample_visible_normal(
+            wo,
+            rng.gen_range(0.0..1.0),
+            rng.gen_range(0.0..1.0),
+        );
+        let wo_dot_h = wo.dot(&h);
+        let wi = h * (2.0 * wo_dot_h) - wo;
+        if wi.z() <= 0.0 {
+            return None;
+        }
 
-        // ラフネスに基づくランダムな方向のずれを追加
-        let scattered_direction = if self.roughness > 0.0 {
-            reflected + random_unit_vector() * self.roughness
-        } else {
-            reflected
-        };
+        // f・cos / pdf = F・G2 / G1(wo)
+        let weight = self.distribution.g2(wo, wi) / self.distribution.g1(wo);
+        let (fresnel, wavelengths) = self.reflection_weight(wo_dot_h, ray_in, rec);
+        Some(ScatterInfo {
+            scattered: Ray::new(rec.point, frame.to_world(wi)).with_wavelengths(wavelengths),
+            attenuation: fresnel * weight,
+            specular: false,
+        })
+    }
+
+    fn eval(&self, ray_in: &Ray, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        if self.distribution.is_smooth() {
+            return None;
+        }
 
-        // 反射方向が表面の裏側を向いている場合は光が吸収される
-        if scattered_direction.dot(&rec.normal) > 0.0 {
-            // 金属性に基づく色の計算
-            let attenuation = self.mix_color(self.base_color);
-
-            Some(ScatterInfo {
-                scattered: Ray::new(rec.point, scattered_direction),
-                attenuation: attenuation * reflection_probability,
-            })
-        } else {
-            None
+        let frame = self.frame(rec);
+        let wo = frame.to_local(-1.0 * ray_in.direction().unit_vector());
+        let wi = frame.to_local(direction);
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return Some(ColorGlam::new(0.0, 0.0, 0.0));
         }
+
+        // f・cos = D・G2・F / (4・cosθo)
+        let h = (wo + wi).unit_vector();
+        let d = self.distribution.d(h);
+        let g = self.distribution.g2(wo, wi);
+        let fresnel = match self.spectral_fresnel(wo.dot(&h), rec, ray_in.wavelengths()) {
+            Some(reflectance) => ray_in
+                .wavelengths()
+                .map_or(ColorGlam::new(1.0, 1.0, 1.0), |w| w.project(reflectance)),
+            None => self.fresnel(wo.dot(&h), rec),
+        };
+        Some(fresnel * (d * g / (4.0 * wo.z())))
+    }
+}
+
+/// RGBの値を各チャンネルの代表波長（650nm, 550nm, 450nm）の間で線形補間
+///
+/// 範囲外の波長では端のチャンネルの値を使用する
+fn interpolate_channels(value: ColorGlam, wavelength: f64) -> f64 {
+    if wavelength >= 650.0 {
+        value.x()
+    } else if wavelength >= 550.0 {
+        let t = (wavelength - 550.0) / 100.0;
+        value.y() + (value.x() - value.y()) * t
+    } else if wavelength >= 450.0 {
+        let t = (wavelength - 450.0) / 100.0;
+        value.z() + (value.y() - value.z()) * t
+    } else {
+        value.z()
     }
 }
@@ -0,0 +1,264 @@
+use crate::types::{HitRecord, orthonormal_basis};
+use crate::vec3_glam::Vec3Glam;
+use std::f64::consts::PI;
+
+/// シェーディング法線をz軸とする局所座標系
+///
+/// x軸はテクスチャ座標uの増加方向に揃える
+pub struct ShadingFrame {
+    tangent: Vec3Glam,
+    bitangent: Vec3Glam,
+    normal: Vec3Glam,
+}
+
+impl ShadingFrame {
+    /// 衝突情報のシェーディング法線と接線から局所座標系を作成
+    pub fn from_hit(rec: &HitRecord) -> Self {
+        let normal = rec.normal;
+        let tangent = rec.dpdu - normal * normal.dot(&rec.dpdu);
+        let tangent = if tangent.length_squared() > 1e-12 {
+            tangent.unit_vector()
+        } else {
+            orthonormal_basis(normal).0
+        };
+        let bitangent = normal.cross(&tangent);
+
+        ShadingFrame {
+            tangent,
+            bitangent,
+            normal,
+        }
+    }
+
+    /// 接線を法線まわりに回転した局所座標系（異方性の向きの調整用）
+    ///
+    /// # Arguments
+    ///
+    /// * `angle` - 回転角（ラジアン）
+    pub fn rotated(self, angle: f64) -> Self {
+        if angle == 0.0 {
+            return self;
+        }
+        let tangent = self.tangent * angle.cos() + self.bitangent * angle.sin();
+        ShadingFrame {
+            tangent,
+            bitangent: self.normal.cross(&tangent),
+            normal: self.normal,
+        }
+    }
+
+    /// ワールド座標のベクトルを局所座標に変換
+    pub fn to_local(&self, v: Vec3Glam) -> Vec3Glam {
+        Vec3Glam::new(
+            v.dot(&self.tangent),
+            v.dot(&self.bitangent),
+            v.dot(&self.normal),
+        )
+    }
+
+    /// 局所座標のベクトルをワールド座標に変換
+    pub fn to_world(&self, v: Vec3Glam) -> Vec3Glam {
+        self.tangent * v.x() + self.bitangent * v.y() + self.normal * v.z()
+    }
+}
+
+/// GGX（Trowbridge-Reitz）マイクロファセット分布
+///
+/// ベクトルはすべてシェーディング法線をz軸とする局所座標で扱う
+#[derive(Debug, Clone, Copy)]
+pub struct GgxDistribution {
+    alpha_x: f64,
+    alpha_y: f64,
+}
+
+impl GgxDistribution {
+    /// これ未満のαは完全鏡面として扱う
+    const MIN_ALPHA: f64 = 1e-3;
+
+    /// 知覚的な粗さ（0.0 ~ 1.0）から分布を作成（α = roughness²）
+    pub fn from_roughness(roughness: f64) -> Self {
+        let alpha = roughness.clamp(0.0, 1.0).powi(2);
+        GgxDistribution {
+            alpha_x: alpha,
+            alpha_y: alpha,
+        }
+    }
+
+    /// 接線方向と従接線方向で粗さの異なる分布にする（ヘアライン加工の金属など）
+    ///
+    /// # Arguments
+    ///
+    /// * `anisotropy` - 異方性の強さ（-1.0 ~ 1.0、正で接線方向に、負で従接線方向に伸びる）
+    pub fn with_anisotropy(self, anisotropy: f64) -> Self {
+        let alpha = (self.alpha_x * self.alpha_y).sqrt();
+        let aspect = (1.0 - 0.9 * anisotropy.abs().min(1.0)).sqrt();
+        let (stretched, squeezed) = ((alpha / aspect).min(1.0), alpha * aspect);
+        if anisotropy >= 0.0 {
+            GgxDistribution {
+                alpha_x: stretched,
+                alpha_y: squeezed,
+            }
+        } else {
+            GgxDistribution {
+                alpha_x: squeezed,
+                alpha_y: stretched,
+            }
+        }
+    }
+
+    /// 完全鏡面とみなせるほど滑らかか
+    pub fn is_smooth(&self) -> bool {
+        self.alpha_x.max(self.alpha_y) < Self::MIN_ALPHA
+    }
+
+    /// マイクロファセット法線の分布関数D(h)
+    pub fn d(&self, h: Vec3Glam) -> f64 {
+        if h.z() <= 0.0 {
+            return 0.0;
+        }
+        let e = (h.x() / self.alpha_x).powi(2) + (h.y() / self.alpha_y).powi(2) + h.z().powi(2);
+        1.0 / (PI * self.alpha_x * self.alpha_y * e * e)
+    }
+
+    /// Smithのマスキング関数の補助関数Λ(w)
+    fn lambda(&self, w: Vec3Glam) -> f64 {
+        let cos2 = w.z() * w.z();
+        if cos2 <= 0.0 {
+            return f64::INFINITY;
+        }
+        let tan2_alpha2 = ((self.alpha_x * w.x()).powi(2) + (self.alpha_y * w.y()).powi(2)) / cos2;
+        0.5 * (-1.0 + (1.0 + tan2_alpha2).sqrt())
+    }
+
+    /// 一方向のマスキング関数G1(w)
+    pub fn g1(&self, w: Vec3Glam) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// 高さ相関のあるマスキング・シャドウイング関数G2(wo, wi)
+    pub fn g2(&self, wo: Vec3Glam, wi: Vec3Glam) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// 可視法線のサンプリングで反射方向wiを得る確率密度
+    ///
+    /// # Arguments
+    ///
+    /// * `wo` - 出射方向
+    /// * `h` - woとwiのハーフベクトル
+    pub fn reflection_pdf(&self, wo: Vec3Glam, h: Vec3Glam) -> f64 {
+        if wo.z() <= 0.0 {
+            return 0.0;
+        }
+        self.g1(wo) * self.d(h) / (4.0 * wo.z())
+    }
+
+    /// 方向woから見えるマイクロファセット法線をサンプリング（Heitz 2018）
+    ///
+    /// # Arguments
+    ///
+    /// * `wo` - 出射方向（z > 0）
+    /// * `u1`, `u2` - [0, 1)の一様乱数
+    pub fn sample_visible_normal(&self, wo: Vec3Glam, u1: f64, u2: f64) -> Vec3Glam {
+        // 分布を単位半球に引き伸ばした空間へ変換
+        let vh = Vec3Glam::new(self.alpha_x * wo.x(), self.alpha_y * wo.y(), wo.z()).unit_vector();
+
+        let len2 = vh.x() * vh.x() + vh.y() * vh.y();
+        let t1 = if len2 > 0.0 {
+            Vec3Glam::new(-vh.y(), vh.x(), 0.0) / len2.sqrt()
+        } else {
+            Vec3Glam::new(1.0, 0.0, 0.0)
+        };
+        let t2 = vh.cross(&t1);
+
+        // 投影された円盤上で一様にサンプリングし、見える半分へ寄せる
+        let r = u1.sqrt();
+        let phi = 2.0 * PI * u2;
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + vh.z());
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+        let nh = t1 * p1 + t2 * p2 + vh * (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt();
+
+        // 元の空間へ戻す
+        Vec3Glam::new(
+            self.alpha_x * nh.x(),
+            self.alpha_y * nh.y(),
+            nh.z().max(1e-6),
+        )
+        .unit_vector()
+    }
+}
+
+/// 布の光沢用のCharlie分布（Estevez & Kulla 2017）
+///
+/// ベクトルはシェーディング法線をz軸とする局所座標で扱う
+#[derive(Debug, Clone, Copy)]
+pub struct CharlieDistribution {
+    alpha: f64,
+}
+
+impl CharlieDistribution {
+    /// 知覚的な粗さ（0.0 ~ 1.0）から分布を作成（α = roughness²）
+    pub fn from_roughness(roughness: f64) -> Self {
+        CharlieDistribution {
+            alpha: roughness.clamp(0.07, 1.0).powi(2),
+        }
+    }
+
+    /// マイクロファセット法線の分布関数D(h)
+    pub fn d(&self, h: Vec3Glam) -> f64 {
+        let inv_alpha = 1.0 / self.alpha;
+        let sin_theta = (1.0 - h.z() * h.z()).max(0.0).sqrt();
+        (2.0 + inv_alpha) * sin_theta.powf(inv_alpha) / (2.0 * PI)
+    }
+
+    /// Ashikhminの可視項 V = G / (4・cosθo・cosθi)
+    pub fn visibility(&self, wo: Vec3Glam, wi: Vec3Glam) -> f64 {
+        1.0 / (4.0 * (wo.z() + wi.z() - wo.z() * wi.z()))
+    }
+}
+
+/// 複素屈折率（n + ik）を持つ導体のフレネル反射率
+///
+/// # Arguments
+///
+/// * `cos_theta` - 入射角の余弦
+/// * `eta` - 屈折率の実部
+/// * `k` - 消衰係数
+pub fn fresnel_conductor(cos_theta: f64, eta: f64, k: f64) -> f64 {
+    let cos2 = cos_theta.clamp(0.0, 1.0).powi(2);
+    let sin2 = 1.0 - cos2;
+
+    let t0 = eta * eta - k * k - sin2;
+    let a2b2 = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+    let t1 = a2b2 + cos2;
+    let a = (0.5 * (a2b2 + t0)).max(0.0).sqrt();
+    let t2 = 2.0 * cos_theta.clamp(0.0, 1.0) * a;
+    let rs = (t1 - t2) / (t1 + t2);
+
+    let t3 = cos2 * a2b2 + sin2 * sin2;
+    let t4 = t2 * sin2;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+
+    0.5 * (rp + rs)
+}
+
+/// 誘電体のフレネル反射率（偏光を考慮しない厳密解）
+///
+/// # Arguments
+///
+/// * `cos_theta_i` - 入射角の余弦
+/// * `eta` - 入射側に対する透過側の相対屈折率
+pub fn fresnel_dielectric(cos_theta_i: f64, eta: f64) -> f64 {
+    let cos_i = cos_theta_i.clamp(0.0, 1.0);
+    let sin2_t = (1.0 - cos_i * cos_i) / (eta * eta);
+    if sin2_t >= 1.0 {
+        // 全反射
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+
+    let rs = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    let rp = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    0.5 * (rs * rs + rp * rp)
+}
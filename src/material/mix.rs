@@ -0,0 +1,120 @@
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::types::{HitRecord, Material, Matte, ScatterInfo};
+use crate::vec3_glam::Vec3Glam;
+use std::sync::Arc;
+
+type ColorGlam = Vec3Glam;
+
+/// 2つのマテリアルを重みに応じて確率的に切り替えるマテリアル
+///
+/// 散乱は衝突ごとに一方のマテリアルを選んで計算し、
+/// 直接光の評価は両方の寄与を重みで混合する。
+/// 選択には衝突点から求めたハッシュを使い、同じ衝突点でのアルファテスト、散乱、
+/// マットやシャドウレイの透過の判定で同じマテリアルが選ばれるようにする
+pub struct Mix {
+    first: Arc<dyn Material>,
+    second: Arc<dyn Material>,
+    /// 輝度を2つ目のマテリアルの割合として使用するテクスチャ
+    weight: Arc<dyn Texture>,
+}
+
+impl Mix {
+    /// 2つのマテリアルを混合したマテリアルを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - 重み0.0で使用するマテリアル
+    /// * `second` - 重み1.0で使用するマテリアル
+    /// * `weight` - 輝度を2つ目のマテリアルの割合（0.0 ~ 1.0）として使用するテクスチャ
+    pub fn new(
+        first: Arc<dyn Material>,
+        second: Arc<dyn Material>,
+        weight: Arc<dyn Texture>,
+    ) -> Self {
+        Mix {
+            first,
+            second,
+            weight,
+        }
+    }
+
+    fn weight(&self, rec: &HitRecord) -> f64 {
+        self.weight.value_at(rec).luminance().clamp(0.0, 1.0)
+    }
+
+    /// 重みに従って一方のマテリアルを選ぶ
+    ///
+    /// 衝突点ごとに決まる値で選ぶため、同じ衝突点に対しては常に同じマテリアルを返す
+    fn choose(&self, rec: &HitRecord) -> &Arc<dyn Material> {
+        if hash_unit(rec) < self.weight(rec) {
+            &self.second
+        } else {
+            &self.first
+        }
+    }
+}
+
+/// 衝突点の座標から[0, 1)の値を求める（SplitMix64の撹拌関数）
+fn hash_unit(rec: &HitRecord) -> f64 {
+    let mix = |mut x: u64| {
+        x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+        x ^ (x >> 31)
+    };
+    let point = rec.point;
+    let hash = [point.x(), point.y(), point.z()]
+        .iter()
+        .fold(0x9e3779b97f4a7c15, |acc: u64, v| {
+            mix(acc ^ v.to_bits()).wrapping_add(0x9e3779b97f4a7c15)
+        });
+    // 上位53ビットを仮数として使う
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl Material for Mix {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
+        // 選択確率と混合の重みが等しいため、減衰率はそのまま使用できる
+        self.choose(rec).scatter(ray_in, rec)
+    }
+
+    fn eval(&self, ray_in: &Ray, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        let weight = self.weight(rec);
+        // デルタ分布の寄与は散乱レイで扱うため、直接光の評価では0とする
+        match (
+            self.first.eval(ray_in, rec, direction),
+            self.second.eval(ray_in, rec, direction),
+        ) {
+            (None, None) => None,
+            (first, second) => {
+                let black = ColorGlam::new(0.0, 0.0, 0.0);
+                Some(first.unwrap_or(black) * (1.0 - weight) + second.unwrap_or(black) * weight)
+            }
+        }
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> ColorGlam {
+        let weight = self.weight(rec);
+        self.first.emitted(rec) * (1.0 - weight) + self.second.emitted(rec) * weight
+    }
+
+    fn alpha_test(&self, rec: &HitRecord) -> bool {
+        self.choose(rec).alpha_test(rec)
+    }
+
+    fn matte(&self, rec: &HitRecord) -> Option<Matte> {
+        self.choose(rec).matte(rec)
+    }
+
+    fn is_interface(&self, rec: &HitRecord) -> bool {
+        self.choose(rec).is_interface(rec)
+    }
+
+    fn is_thin(&self, rec: &HitRecord) -> bool {
+        self.choose(rec).is_thin(rec)
+    }
+
+    fn shadow_transmittance(&self, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        self.choose(rec).shadow_transmittance(rec, direction)
+    }
+}
@@ -1,7 +1,30 @@
+pub mod alpha;
+pub mod coated;
 pub mod dielectric;
+pub mod interface;
 pub mod lambertian;
+pub mod matte;
 pub mod metal;
+pub mod microfacet;
+pub mod mix;
+pub mod normal_map;
+pub mod principled;
+pub mod sheen;
+pub mod subsurface;
+pub mod thin_dielectric;
+pub mod thin_film;
 
-pub use dielectric::Dielectric;
+pub use alpha::{AlphaMasked, AlphaMode};
+pub use coated::Coated;
+pub use dielectric::{Dielectric, Dispersion};
+pub use interface::Interface;
 pub use lambertian::Lambertian;
-pub use metal::Metal;
+pub use matte::{Holdout, ShadowCatcher};
+pub use metal::{ConductorFresnel, Metal, MetalPreset};
+pub use mix::Mix;
+pub use normal_map::{NormalMapped, NormalPerturbation};
+pub use principled::{Principled, PrincipledParams};
+pub use sheen::Sheen;
+pub use subsurface::Subsurface;
+pub use thin_dielectric::ThinDielectric;
+pub use thin_film::ThinFilm;
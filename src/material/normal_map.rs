@@ -0,0 +1,176 @@
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::types::{HitRecord, Material, Matte, ScatterInfo, orthonormal_basis};
+use crate::vec3_glam::Vec3Glam;
+use std::sync::Arc;
+
+type ColorGlam = Vec3Glam;
+
+/// 表面の法線を摂動させる方法
+pub enum NormalPerturbation {
+    /// 接空間の法線マップ（RGBを-1.0 ~ 1.0のXYZとして解釈）
+    NormalMap {
+        texture: Arc<dyn Texture>,
+        /// 法線の傾きの強さ
+        strength: f64,
+        /// 緑チャンネルを反転するか（DirectX形式の法線マップ用）
+        flip_y: bool,
+    },
+    /// 輝度を高さとして扱うバンプマップ
+    BumpMap {
+        texture: Arc<dyn Texture>,
+        /// 輝度1.0に対応するワールド座標での高さ
+        strength: f64,
+    },
+}
+
+/// 法線を摂動させてから内側のマテリアルで散乱を計算するマテリアル
+pub struct NormalMapped {
+    inner: Arc<dyn Material>,
+    perturbation: NormalPerturbation,
+}
+
+impl NormalMapped {
+    /// 法線マップまたはバンプマップを適用したマテリアルを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - 摂動後の法線で散乱を計算するマテリアル
+    /// * `perturbation` - 法線の摂動方法
+    pub fn new(inner: Arc<dyn Material>, perturbation: NormalPerturbation) -> Self {
+        NormalMapped {
+            inner,
+            perturbation,
+        }
+    }
+
+    /// 法線を摂動させた衝突情報を作成
+    fn perturb(&self, rec: &HitRecord) -> HitRecord {
+        // 裏面に当たった場合も表側の法線を基準に計算する
+        let outward = if rec.front_face {
+            rec.normal
+        } else {
+            -1.0 * rec.normal
+        };
+
+        let normal = match &self.perturbation {
+            NormalPerturbation::NormalMap {
+                texture,
+                strength,
+                flip_y,
+            } => Self::apply_normal_map(rec, outward, texture.as_ref(), *strength, *flip_y),
+            NormalPerturbation::BumpMap { texture, strength } => {
+                Self::apply_bump_map(rec, outward, texture.as_ref(), *strength)
+            }
+        };
+
+        let mut perturbed = rec.clone();
+        perturbed.normal = if rec.front_face {
+            normal
+        } else {
+            -1.0 * normal
+        };
+        perturbed
+    }
+
+    fn apply_normal_map(
+        rec: &HitRecord,
+        outward: Vec3Glam,
+        texture: &dyn Texture,
+        strength: f64,
+        flip_y: bool,
+    ) -> Vec3Glam {
+        // 接空間の基底（T: uの増加方向、B: vの増加方向、N: 法線）
+        let tangent = rec.dpdu - outward * outward.dot(&rec.dpdu);
+        let tangent = if tangent.length_squared() > 1e-12 {
+            tangent.unit_vector()
+        } else {
+            orthonormal_basis(outward).0
+        };
+        let mut bitangent = outward.cross(&tangent);
+        if bitangent.dot(&rec.dpdv) < 0.0 {
+            bitangent = -1.0 * bitangent;
+        }
+
+        let color = texture.value_at(rec);
+        let x = (2.0 * color.x() - 1.0) * strength;
+        let y = (2.0 * color.y() - 1.0) * strength * if flip_y { -1.0 } else { 1.0 };
+        let z = (2.0 * color.z() - 1.0).max(0.0);
+
+        let normal = tangent * x + bitangent * y + outward * z;
+        if normal.length_squared() < 1e-12 {
+            outward
+        } else {
+            normal.unit_vector()
+        }
+    }
+
+    fn apply_bump_map(
+        rec: &HitRecord,
+        outward: Vec3Glam,
+        texture: &dyn Texture,
+        strength: f64,
+    ) -> Vec3Glam {
+        // 高さの偏微分を差分で近似（フットプリントが大きいほど広い範囲で平均化する）
+        let delta = (0.5 * rec.footprint).max(0.0005);
+        let height = |du: f64, dv: f64| {
+            let point = rec.point + rec.dpdu * du + rec.dpdv * dv;
+            texture
+                .value_filtered(rec.u + du, rec.v + dv, point, rec.footprint)
+                .luminance()
+                * strength
+        };
+        let base = height(0.0, 0.0);
+        let dhdu = (height(delta, 0.0) - base) / delta;
+        let dhdv = (height(0.0, delta) - base) / delta;
+
+        // 高さ方向に変位させた表面の接線から法線を求める
+        let dpdu = rec.dpdu + outward * dhdu;
+        let dpdv = rec.dpdv + outward * dhdv;
+        let normal = dpdu.cross(&dpdv);
+        if normal.length_squared() < 1e-12 {
+            return outward;
+        }
+        let normal = normal.unit_vector();
+        if normal.dot(&outward) < 0.0 {
+            -1.0 * normal
+        } else {
+            normal
+        }
+    }
+}
+
+impl Material for NormalMapped {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
+        self.inner.scatter(ray_in, &self.perturb(rec))
+    }
+
+    fn eval(&self, ray_in: &Ray, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        self.inner.eval(ray_in, &self.perturb(rec), direction)
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> ColorGlam {
+        self.inner.emitted(rec)
+    }
+
+    fn alpha_test(&self, rec: &HitRecord) -> bool {
+        self.inner.alpha_test(rec)
+    }
+
+    fn matte(&self, rec: &HitRecord) -> Option<Matte> {
+        self.inner.matte(rec)
+    }
+
+    fn is_interface(&self, rec: &HitRecord) -> bool {
+        self.inner.is_interface(rec)
+    }
+
+    fn is_thin(&self, rec: &HitRecord) -> bool {
+        self.inner.is_thin(rec)
+    }
+
+    fn shadow_transmittance(&self, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        self.inner
+            .shadow_transmittance(&self.perturb(rec), direction)
+    }
+}
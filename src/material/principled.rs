@@ -0,0 +1,328 @@
+use crate::ray::Ray;
+use crate::texture::{SolidColor, Texture};
+use crate::types::{HitRecord, Material, ScatterInfo};
+use crate::vec3_glam::Vec3Glam;
+use rand::Rng;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use super::dielectric::Dielectric;
+use super::microfacet::{GgxDistribution, ShadingFrame};
+
+type ColorGlam = Vec3Glam;
+
+/// プリンシプルドマテリアルのパラメータ
+///
+/// glTFやOBJなどからエクスポートされたマテリアルの値をそのまま対応付けられる
+pub struct PrincipledParams {
+    /// 基本色（拡散反射色、金属の場合は反射色）
+    pub base_color: Arc<dyn Texture>,
+    /// 金属性（0.0 ~ 1.0）
+    pub metallic: f64,
+    /// 表面の粗さ（0.0 ~ 1.0）
+    pub roughness: f64,
+    /// 非金属の鏡面反射の強さ（0.5で反射率4%）
+    pub specular: f64,
+    /// クリアコート層の強さ（0.0 ~ 1.0）
+    pub clearcoat: f64,
+    /// クリアコート層の粗さ（0.0 ~ 1.0）
+    pub clearcoat_roughness: f64,
+    /// 布などのふちに現れる光沢の強さ（0.0 ~ 1.0）
+    pub sheen: f64,
+    /// シーン（ふちの光沢）の色を基本色に近づける割合（0.0 ~ 1.0）
+    pub sheen_tint: f64,
+    /// 透過の割合（0.0 ~ 1.0）
+    pub transmission: f64,
+    /// 透過に使用する屈折率
+    pub ior: f64,
+    /// 放射色
+    pub emission: Arc<dyn Texture>,
+    /// 放射の強さ
+    pub emission_strength: f64,
+}
+
+impl Default for PrincipledParams {
+    fn default() -> Self {
+        PrincipledParams {
+            base_color: Arc::new(SolidColor::new(ColorGlam::new(0.8, 0.8, 0.8))),
+            metallic: 0.0,
+            roughness: 0.5,
+            specular: 0.5,
+            clearcoat: 0.0,
+            clearcoat_roughness: 0.03,
+            sheen: 0.0,
+            sheen_tint: 0.5,
+            transmission: 0.0,
+            ior: 1.5,
+            emission: Arc::new(SolidColor::new(ColorGlam::new(0.0, 0.0, 0.0))),
+            emission_strength: 0.0,
+        }
+    }
+}
+
+/// 拡散反射・鏡面反射・クリアコート・シーン・透過・発光を重ね合わせたマテリアル
+///
+/// Disney BSDFに倣い、各ローブを確率的に選んでサンプリングする。
+/// クリアコート層の下に鏡面反射と透過、鏡面反射の下に拡散反射が重なり、
+/// 下の層へ届く光は上の層のフレネル反射率の残りとなる。
+/// 反射のローブは全ローブの確率密度の混合で重み付けし、透過は誘電体として扱う。
+/// 粗さが十分小さい鏡面反射とクリアコートは完全鏡面のローブとして扱う
+pub struct Principled {
+    params: PrincipledParams,
+    specular: GgxDistribution,
+    clearcoat: GgxDistribution,
+    transmission: Dielectric,
+}
+
+/// 各衝突点で評価したローブの重み
+struct Lobes {
+    base_color: ColorGlam,
+    /// 鏡面反射の垂直入射での反射率
+    f0: ColorGlam,
+    diffuse: f64,
+    specular: f64,
+    clearcoat: f64,
+    transmission: f64,
+}
+
+impl Lobes {
+    /// 入射角の余弦に対する鏡面反射のフレネル反射率（Schlickの近似）
+    fn specular_fresnel(&self, cos_theta: f64) -> ColorGlam {
+        let schlick = (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5);
+        self.f0 + (ColorGlam::new(1.0, 1.0, 1.0) - self.f0) * schlick
+    }
+
+    /// 入射角の余弦に対するクリアコート層の反射率（屈折率1.5、反射率4%）
+    fn coat_fresnel(&self, cos_theta: f64) -> f64 {
+        let schlick = (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5);
+        self.clearcoat * (0.04 + 0.96 * schlick)
+    }
+}
+
+impl Principled {
+    pub fn new(params: PrincipledParams) -> Self {
+        let specular = GgxDistribution::from_roughness(params.roughness);
+        let clearcoat = GgxDistribution::from_roughness(params.clearcoat_roughness);
+        let transmission = Dielectric::new(params.ior, params.roughness);
+
+        Principled {
+            params,
+            specular,
+            clearcoat,
+            transmission,
+        }
+    }
+
+    fn lobes(&self, rec: &HitRecord) -> Lobes {
+        let p = &self.params;
+        let base_color = p.base_color.value_at(rec);
+        let metallic = p.metallic.clamp(0.0, 1.0);
+        let transmission = (1.0 - metallic) * p.transmission.clamp(0.0, 1.0);
+
+        // 非金属の反射率（specular 0.5で4%）と基本色を金属性で補間
+        let dielectric_f0 = 0.08 * p.specular.clamp(0.0, 1.0);
+        let f0 = ColorGlam::new(dielectric_f0, dielectric_f0, dielectric_f0) * (1.0 - metallic)
+            + base_color * metallic;
+
+        Lobes {
+            base_color,
+            f0,
+            diffuse: (1.0 - metallic) * (1.0 - transmission),
+            // 透過する割合の鏡面反射は誘電体のローブで扱う
+            specular: 1.0 - transmission,
+            clearcoat: p.clearcoat.clamp(0.0, 1.0),
+            transmission,
+        }
+    }
+
+    /// 各ローブを選ぶ確率（拡散反射、鏡面反射、クリアコート、透過の順）
+    ///
+    /// 視線方向での層による減衰を、評価する値と同じように見込む
+    fn lobe_probabilities(lobes: &Lobes, cos_theta: f64) -> [f64; 4] {
+        let base = 1.0 - lobes.coat_fresnel(cos_theta);
+        let below_specular = 1.0 - lobes.specular_fresnel(cos_theta).luminance();
+        let weights = [
+            lobes.diffuse * lobes.base_color.luminance().max(0.05) * below_specular * base,
+            // グレージング角での反射率の増加を見込んで底上げする
+            lobes.specular * (lobes.f0.luminance() + 0.25) * base,
+            0.25 * lobes.clearcoat,
+            lobes.transmission * base,
+        ];
+        let total: f64 = weights.iter().sum();
+        weights.map(|w| w / total)
+    }
+
+    /// 反射のローブのうち完全鏡面でないものの合計 f・cos を局所座標で評価
+    ///
+    /// 拡散反射は鏡面反射の、鏡面反射と拡散反射はクリアコート層の
+    /// 視線方向でのフレネル反射率の残りを掛ける
+    fn eval_reflection(&self, lobes: &Lobes, wo: Vec3Glam, wi: Vec3Glam) -> ColorGlam {
+        let black = ColorGlam::new(0.0, 0.0, 0.0);
+        let white = ColorGlam::new(1.0, 1.0, 1.0);
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return black;
+        }
+        let h = (wo + wi).unit_vector();
+        let wi_dot_h = wi.dot(&h);
+        let schlick = (1.0 - wi_dot_h.clamp(0.0, 1.0)).powi(5);
+        let base = 1.0 - lobes.coat_fresnel(wo.z());
+
+        // 拡散反射とシーン
+        let tint = if lobes.base_color.luminance() > 0.0 {
+            lobes.base_color / lobes.base_color.luminance()
+        } else {
+            white
+        };
+        let sheen_color = white * (1.0 - self.params.sheen_tint) + tint * self.params.sheen_tint;
+        let diffuse = (lobes.base_color * (1.0 / PI) + sheen_color * (self.params.sheen * schlick))
+            * (white - lobes.specular_fresnel(wo.z()))
+            * (lobes.diffuse * wi.z() * base);
+
+        // 鏡面反射
+        let specular = if self.specular.is_smooth() {
+            black
+        } else {
+            lobes.specular_fresnel(wi_dot_h)
+                * (lobes.specular * self.specular.d(h) * self.specular.g2(wo, wi) / (4.0 * wo.z())
+                    * base)
+        };
+
+        // クリアコート
+        let clearcoat = if self.clearcoat.is_smooth() {
+            0.0
+        } else {
+            lobes.coat_fresnel(wi_dot_h) * self.clearcoat.d(h) * self.clearcoat.g2(wo, wi)
+                / (4.0 * wo.z())
+        };
+
+        diffuse + specular + ColorGlam::new(clearcoat, clearcoat, clearcoat)
+    }
+
+    /// 完全鏡面でない反射のローブの混合確率密度
+    fn reflection_pdf(&self, probabilities: &[f64; 4], wo: Vec3Glam, wi: Vec3Glam) -> f64 {
+        if wi.z() <= 0.0 {
+            return 0.0;
+        }
+        let h = (wo + wi).unit_vector();
+        let mut pdf = probabilities[0] * wi.z() / PI;
+        if !self.specular.is_smooth() {
+            pdf += probabilities[1] * self.specular.reflection_pdf(wo, h);
+        }
+        if !self.clearcoat.is_smooth() {
+            pdf += probabilities[2] * self.clearcoat.reflection_pdf(wo, h);
+        }
+        pdf
+    }
+}
+
+impl Material for Principled {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
+        let lobes = self.lobes(rec);
+
+        // 透過する物体の内側では誘電体として振る舞う
+        if !rec.front_face && lobes.transmission > 0.0 {
+            return self.transmission.scatter(ray_in, rec);
+        }
+
+        let frame = ShadingFrame::from_hit(rec);
+        let wo = frame.to_local(-1.0 * ray_in.direction().unit_vector());
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        let probabilities = Self::lobe_probabilities(&lobes, wo.z());
+        let mut rng = rand::thread_rng();
+        let choice = rng.gen_range(0.0..1.0);
+        let base = 1.0 - lobes.coat_fresnel(wo.z());
+
+        // 透過のローブ
+        if choice >= probabilities[0] + probabilities[1] + probabilities[2] {
+            let scatter = self.transmission.scatter(ray_in, rec)?;
+            return Some(ScatterInfo {
+                attenuation: scatter.attenuation
+                    * lobes.base_color
+                    * (lobes.transmission * base / probabilities[3]),
+                ..scatter
+            });
+        }
+
+        // 完全鏡面のローブは確率で割った重みで鏡面反射の方向へ散乱する
+        let specular_lobe =
+            choice >= probabilities[0] && choice < probabilities[0] + probabilities[1];
+        let clearcoat_lobe = choice >= probabilities[0] + probabilities[1];
+        let mirror = Vec3Glam::new(-wo.x(), -wo.y(), wo.z());
+        if specular_lobe && self.specular.is_smooth() {
+            return Some(ScatterInfo {
+                scattered: Ray::new(rec.point, frame.to_world(mirror)),
+                attenuation: lobes.specular_fresnel(wo.z())
+                    * (lobes.specular * base / probabilities[1]),
+                specular: true,
+            });
+        }
+        if clearcoat_lobe && self.clearcoat.is_smooth() {
+            let weight = lobes.coat_fresnel(wo.z()) / probabilities[2];
+            return Some(ScatterInfo {
+                scattered: Ray::new(rec.point, frame.to_world(mirror)),
+                attenuation: ColorGlam::new(weight, weight, weight),
+                specular: true,
+            });
+        }
+
+        // 反射のローブから方向を選ぶ
+        let (u1, u2): (f64, f64) = (rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0));
+        let wi = if choice < probabilities[0] {
+            // コサイン重み付きの半球サンプリング
+            let r = u1.sqrt();
+            let phi = 2.0 * PI * u2;
+            Vec3Glam::new(r * phi.cos(), r * phi.sin(), (1.0 - u1).max(0.0).sqrt())
+        } else {
+            let distribution = if specular_lobe {
+                &self.specular
+            } else {
+                &self.clearcoat
+            };
+            let h = distribution.sample_visible_normal(wo, u1, u2);
+            h * (2.0 * wo.dot(&h)) - wo
+        };
+
+        let pdf = self.reflection_pdf(&probabilities, wo, wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(ScatterInfo {
+            scattered: Ray::new(rec.point, frame.to_world(wi)),
+            attenuation: self.eval_reflection(&lobes, wo, wi) / pdf,
+            specular: false,
+        })
+    }
+
+    fn eval(&self, ray_in: &Ray, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        let lobes = self.lobes(rec);
+        if !rec.front_face && lobes.transmission > 0.0 {
+            return self.transmission.eval(ray_in, rec, direction);
+        }
+
+        // 透過と完全鏡面のローブは散乱レイで扱うため、それ以外の反射のローブのみを評価
+        let frame = ShadingFrame::from_hit(rec);
+        let wo = frame.to_local(-1.0 * ray_in.direction().unit_vector());
+        let wi = frame.to_local(direction);
+        let mut value = self.eval_reflection(&lobes, wo, wi);
+
+        // 透過のローブのうち粗い表面での反射（散乱と同じ重みを掛ける）
+        if lobes.transmission > 0.0
+            && let Some(reflection) = self.transmission.eval(ray_in, rec, direction)
+        {
+            let base = 1.0 - lobes.coat_fresnel(wo.z());
+            value = value + reflection * lobes.base_color * (lobes.transmission * base);
+        }
+        Some(value)
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> ColorGlam {
+        if !rec.front_face || self.params.emission_strength <= 0.0 {
+            return ColorGlam::new(0.0, 0.0, 0.0);
+        }
+        self.params.emission.value_at(rec) * self.params.emission_strength
+    }
+}
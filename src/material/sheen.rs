@@ -0,0 +1,169 @@
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::types::{HitRecord, Material, Matte, ScatterInfo};
+use crate::vec3_glam::Vec3Glam;
+use rand::Rng;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use super::microfacet::{CharlieDistribution, ShadingFrame};
+
+type ColorGlam = Vec3Glam;
+
+/// 方向アルベドの表の分割数（cosθoについて）
+const ALBEDO_TABLE_SIZE: usize = 32;
+
+/// 内側のマテリアルの上に布の光沢（シーン）の層を重ねるマテリアル
+///
+/// 光沢はCharlie分布とAshikhminの可視項で表し、
+/// 光沢の層で反射した分だけ内側のマテリアルの寄与を減らす（glTFのKHR_materials_sheenと同様）
+pub struct Sheen {
+    inner: Arc<dyn Material>,
+    color: Arc<dyn Texture>,
+    distribution: CharlieDistribution,
+    /// 光沢の層の方向アルベド（cosθoごと、光沢色が白の場合）
+    albedo: [f64; ALBEDO_TABLE_SIZE],
+}
+
+impl Sheen {
+    /// 光沢の層を重ねたマテリアルを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - 光沢の下のマテリアル
+    /// * `color` - 光沢の色
+    /// * `roughness` - 光沢の粗さ（0.0 ~ 1.0、大きいほど広い角度で光る）
+    pub fn new(inner: Arc<dyn Material>, color: Arc<dyn Texture>, roughness: f64) -> Self {
+        let distribution = CharlieDistribution::from_roughness(roughness);
+        Sheen {
+            inner,
+            color,
+            distribution,
+            albedo: Self::albedo_table(&distribution),
+        }
+    }
+
+    /// 方向アルベドを半球上の数値積分で計算
+    fn albedo_table(distribution: &CharlieDistribution) -> [f64; ALBEDO_TABLE_SIZE] {
+        let (theta_steps, phi_steps) = (32, 64);
+        let mut table = [0.0; ALBEDO_TABLE_SIZE];
+        for (i, albedo) in table.iter_mut().enumerate() {
+            let cos_o = (i as f64 + 0.5) / ALBEDO_TABLE_SIZE as f64;
+            let wo = Vec3Glam::new((1.0 - cos_o * cos_o).sqrt(), 0.0, cos_o);
+
+            // cosθiについて一様に分割すると立体角の要素は dcosθ・dφ となる
+            let mut sum = 0.0;
+            for j in 0..theta_steps {
+                let cos_i = (j as f64 + 0.5) / theta_steps as f64;
+                let sin_i = (1.0 - cos_i * cos_i).sqrt();
+                for k in 0..phi_steps {
+                    let phi = 2.0 * PI * (k as f64 + 0.5) / phi_steps as f64;
+                    let wi = Vec3Glam::new(sin_i * phi.cos(), sin_i * phi.sin(), cos_i);
+                    sum += Self::eval_lobe(distribution, wo, wi);
+                }
+            }
+            *albedo = (sum * 2.0 * PI / (theta_steps * phi_steps) as f64).min(1.0);
+        }
+        table
+    }
+
+    /// 光沢の層の f・cos（光沢色を除く）
+    fn eval_lobe(distribution: &CharlieDistribution, wo: Vec3Glam, wi: Vec3Glam) -> f64 {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return 0.0;
+        }
+        let h = (wo + wi).unit_vector();
+        distribution.d(h) * distribution.visibility(wo, wi) * wi.z()
+    }
+
+    /// 方向woでの光沢の層の方向アルベド
+    fn directional_albedo(&self, cos_o: f64) -> f64 {
+        let index = (cos_o.clamp(0.0, 1.0) * ALBEDO_TABLE_SIZE as f64) as usize;
+        self.albedo[index.min(ALBEDO_TABLE_SIZE - 1)]
+    }
+
+    /// 光沢の層で反射されずに内側のマテリアルへ届く割合
+    fn inner_scale(color: ColorGlam, albedo: f64) -> f64 {
+        1.0 - color.x().max(color.y()).max(color.z()) * albedo
+    }
+}
+
+impl Material for Sheen {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
+        let frame = ShadingFrame::from_hit(rec);
+        let wo = frame.to_local(-1.0 * ray_in.direction().unit_vector());
+        if !rec.front_face || wo.z() <= 0.0 {
+            return self.inner.scatter(ray_in, rec);
+        }
+
+        let color = self.color.value_at(rec);
+        let albedo = self.directional_albedo(wo.z());
+        let inner_scale = Self::inner_scale(color, albedo);
+        let sheen_probability = (1.0 - inner_scale).clamp(0.0, 0.5);
+
+        let mut rng = rand::thread_rng();
+        if rng.gen_range(0.0..1.0) >= sheen_probability {
+            let scatter = self.inner.scatter(ray_in, rec)?;
+            return Some(ScatterInfo {
+                attenuation: scatter.attenuation * (inner_scale / (1.0 - sheen_probability)),
+                ..scatter
+            });
+        }
+
+        // 光沢の層はコサイン重み付きの半球サンプリングで散乱させる
+        let (u1, u2): (f64, f64) = (rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0));
+        let r = u1.sqrt();
+        let phi = 2.0 * PI * u2;
+        let wi = Vec3Glam::new(r * phi.cos(), r * phi.sin(), (1.0 - u1).max(0.0).sqrt());
+        if wi.z() <= 0.0 {
+            return None;
+        }
+        let pdf = wi.z() / PI;
+
+        Some(ScatterInfo {
+            scattered: Ray::new(rec.point, frame.to_world(wi)),
+            attenuation: color
+                * (Self::eval_lobe(&self.distribution, wo, wi) / (pdf * sheen_probability)),
+            specular: false,
+        })
+    }
+
+    fn eval(&self, ray_in: &Ray, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        let inner = self.inner.eval(ray_in, rec, direction);
+        let frame = ShadingFrame::from_hit(rec);
+        let wo = frame.to_local(-1.0 * ray_in.direction().unit_vector());
+        if !rec.front_face || wo.z() <= 0.0 {
+            return inner;
+        }
+
+        // 内側のマテリアルがデルタ分布の場合、その寄与は散乱レイで扱う
+        let color = self.color.value_at(rec);
+        let inner_scale = Self::inner_scale(color, self.directional_albedo(wo.z()));
+        let sheen = color * Self::eval_lobe(&self.distribution, wo, frame.to_local(direction));
+        Some(inner.map_or(sheen, |inner| inner * inner_scale + sheen))
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> ColorGlam {
+        self.inner.emitted(rec)
+    }
+
+    fn alpha_test(&self, rec: &HitRecord) -> bool {
+        self.inner.alpha_test(rec)
+    }
+
+    fn matte(&self, rec: &HitRecord) -> Option<Matte> {
+        self.inner.matte(rec)
+    }
+
+    fn is_interface(&self, rec: &HitRecord) -> bool {
+        self.inner.is_interface(rec)
+    }
+
+    fn is_thin(&self, rec: &HitRecord) -> bool {
+        self.inner.is_thin(rec)
+    }
+
+    fn shadow_transmittance(&self, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        self.inner.shadow_transmittance(rec, direction)
+    }
+}
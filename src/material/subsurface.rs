@@ -0,0 +1,91 @@
+use crate::ray::{Ray, RayMedium};
+use crate::types::{HitRecord, Material, ScatterInfo, random_unit_vector};
+use crate::vec3_glam::Vec3Glam;
+use crate::volumetric::{HomogeneousMedium, Medium};
+use rand::Rng;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use super::microfacet::fresnel_dielectric;
+
+type ColorGlam = Vec3Glam;
+
+/// ランダムウォークによる表面下散乱のマテリアル（肌、ろう、大理石、牛乳など）
+///
+/// 表面ではフレネル反射率に従って鏡面反射し、残りの光は内部の散乱媒質に入る。
+/// 媒質内のランダムウォークはインテグレータが行い、
+/// 裏面に到達した光は拡散透過で外へ出る（出口で直接光を評価する）
+pub struct Subsurface {
+    medium: Arc<dyn Medium>,
+    /// 表面の屈折率
+    ir: f64,
+}
+
+impl Subsurface {
+    /// 表面下散乱のマテリアルを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `medium` - 内部の散乱媒質
+    /// * `ir` - 表面の屈折率
+    pub fn new(medium: HomogeneousMedium, ir: f64) -> Self {
+        Subsurface {
+            // 内部からは表面に遮られて光源が見えない
+            medium: Arc::new(medium.with_enclosed(true)),
+            ir,
+        }
+    }
+
+    /// 法線側の半球へのコサイン重み付きの方向
+    fn cosine_direction(normal: Vec3Glam) -> Vec3Glam {
+        let direction = normal + random_unit_vector();
+        if direction.length_squared() < 1e-8 {
+            normal
+        } else {
+            direction.unit_vector()
+        }
+    }
+}
+
+impl Material for Subsurface {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
+        let white = ColorGlam::new(1.0, 1.0, 1.0);
+
+        // 内側から境界に到達した光は拡散透過で外へ出る
+        if !rec.front_face {
+            return Some(ScatterInfo {
+                scattered: Ray::new(rec.point, Self::cosine_direction(-1.0 * rec.normal)),
+                attenuation: white,
+                specular: false,
+            });
+        }
+
+        let unit_direction = ray_in.direction().unit_vector();
+        let cos_theta = (-1.0 * unit_direction).dot(&rec.normal);
+        if fresnel_dielectric(cos_theta, self.ir) > rand::thread_rng().gen_range(0.0..1.0) {
+            // 表面での鏡面反射
+            return Some(ScatterInfo {
+                scattered: Ray::new(rec.point, unit_direction.reflect(&rec.normal)),
+                attenuation: white,
+                specular: true,
+            });
+        }
+
+        // 内部の媒質へ拡散的に入射する（反射しなかった確率でフレネル透過率は相殺される）
+        Some(ScatterInfo {
+            scattered: Ray::new(rec.point, Self::cosine_direction(-1.0 * rec.normal))
+                .with_medium(RayMedium::Object(self.medium.clone())),
+            attenuation: white,
+            specular: true,
+        })
+    }
+
+    fn eval(&self, _ray_in: &Ray, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        // 表面の反射は鏡面反射のため、直接光は出口の拡散透過でのみ評価する
+        if rec.front_face {
+            return None;
+        }
+        let cosine = (-1.0 * rec.normal).dot(&direction).max(0.0);
+        Some(ColorGlam::new(1.0, 1.0, 1.0) * (cosine / PI))
+    }
+}
@@ -0,0 +1,108 @@
+use crate::ray::Ray;
+use crate::types::{HitRecord, Material, ScatterInfo};
+use crate::vec3_glam::Vec3Glam;
+
+use super::dielectric::{choose_reflection, choose_reflection_spectral};
+use super::microfacet::fresnel_dielectric;
+use super::thin_film::ThinFilm;
+
+type ColorGlam = Vec3Glam;
+
+/// 厚みを持たない1枚の面として扱う誘電体の板（窓ガラス、石けん膜など）
+///
+/// 透過した光は屈折による平行移動をせずにそのまま進み、
+/// 板の内部での多重反射は反射率と透過率にまとめて含める
+pub struct ThinDielectric {
+    /// 板の屈折率
+    ir: f64,
+    /// 干渉を起こすほど薄い膜として扱う場合の膜厚（nm）
+    film_thickness: Option<f64>,
+}
+
+impl ThinDielectric {
+    /// 薄い誘電体の板を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `ir` - 板の屈折率
+    pub fn new(ir: f64) -> Self {
+        ThinDielectric {
+            ir,
+            film_thickness: None,
+        }
+    }
+
+    /// 板を光の波長程度の厚さの膜として扱い、干渉による色を付ける（石けん膜など）
+    ///
+    /// # Arguments
+    ///
+    /// * `thickness` - 膜厚（nm）
+    pub fn with_film_thickness(mut self, thickness: f64) -> Self {
+        self.film_thickness = Some(thickness);
+        self
+    }
+
+    /// 板全体としての反射率
+    fn reflectance(&self, cos_theta: f64) -> ColorGlam {
+        match self.film_thickness {
+            // 空気に挟まれた薄膜として干渉を計算する
+            Some(thickness) => {
+                ThinFilm::new(thickness, self.ir).reflectance(cos_theta, |_| (1.0, 0.0))
+            }
+            None => {
+                // 表裏の界面での反射を無限回足し合わせる R + T²R / (1 - R²) = 2R / (1 + R)
+                let r = fresnel_dielectric(cos_theta, self.ir);
+                let r = if r < 1.0 { 2.0 * r / (1.0 + r) } else { 1.0 };
+                ColorGlam::new(r, r, r)
+            }
+        }
+    }
+}
+
+impl Material for ThinDielectric {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo> {
+        let unit_direction = ray_in.direction().unit_vector();
+        let cos_theta = (-unit_direction).dot(&rec.normal).min(1.0);
+
+        // 干渉のある膜では、波長の組を追跡するパスは波長ごとの反射率を使う
+        // （透過しても方向は変わらないため、随伴波長は打ち切らない）
+        let (reflect, weight, wavelengths) = match (self.film_thickness, ray_in.wavelengths()) {
+            (Some(thickness), Some(wavelengths)) => {
+                let film = ThinFilm::new(thickness, self.ir);
+                let reflectance = wavelengths
+                    .map(|wavelength| film.reflectance_at(cos_theta, (1.0, 0.0), wavelength));
+                let (reflect, factors) = choose_reflection_spectral(reflectance);
+                (
+                    reflect,
+                    ColorGlam::new(1.0, 1.0, 1.0),
+                    Some(wavelengths.scaled(factors)),
+                )
+            }
+            _ => {
+                let (reflect, weight) = choose_reflection(self.reflectance(cos_theta));
+                (reflect, weight, None)
+            }
+        };
+        let direction = if reflect {
+            unit_direction.reflect(&rec.normal)
+        } else {
+            unit_direction
+        };
+
+        Some(ScatterInfo {
+            scattered: Ray::new(rec.point, direction).with_wavelengths(wavelengths),
+            attenuation: weight,
+            specular: true,
+        })
+    }
+
+    fn is_thin(&self, _rec: &HitRecord) -> bool {
+        true
+    }
+
+    /// 板を透過する割合（1 - 反射率）
+    fn shadow_transmittance(&self, rec: &HitRecord, direction: Vec3Glam) -> Option<ColorGlam> {
+        let cos_theta = direction.unit_vector().dot(&rec.normal).abs().min(1.0);
+        Some(ColorGlam::new(1.0, 1.0, 1.0) - self.reflectance(cos_theta))
+    }
+}
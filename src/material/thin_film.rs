@@ -0,0 +1,170 @@
+use crate::spectrum::{MAX_WAVELENGTH, MIN_WAVELENGTH, wavelength_weight};
+use crate::vec3_glam::Vec3Glam;
+use std::f64::consts::PI;
+use std::ops::{Add, Div, Mul, Sub};
+
+type ColorGlam = Vec3Glam;
+
+/// RGBの反射率を求める際に積分する波長の数
+const SPECTRAL_SAMPLES: usize = 16;
+
+/// 表面を覆う薄膜（石けん膜、油膜、金属の酸化膜など）
+///
+/// 膜の上下の界面で反射した光の干渉により、角度と膜厚に応じた虹色の反射になる
+#[derive(Debug, Clone, Copy)]
+pub struct ThinFilm {
+    /// 膜厚（nm）
+    pub thickness: f64,
+    /// 膜の屈折率
+    pub ior: f64,
+}
+
+impl ThinFilm {
+    pub fn new(thickness: f64, ior: f64) -> Self {
+        ThinFilm { thickness, ior }
+    }
+
+    /// 可視光の範囲で積分した、薄膜を通したRGBの反射率
+    ///
+    /// 波長が決まっているパスでは波長ごとに`reflectance_at`を使用する
+    ///
+    /// # Arguments
+    ///
+    /// * `cos_theta` - 入射角の余弦
+    /// * `substrate` - 波長（nm）から下地の複素屈折率（n, k）を返す関数
+    pub fn reflectance(&self, cos_theta: f64, substrate: impl Fn(f64) -> (f64, f64)) -> ColorGlam {
+        // 同じ波長の重みの和で割り、反射率が波長によらず1なら正確に白になるようにする
+        let step = (MAX_WAVELENGTH - MIN_WAVELENGTH) / SPECTRAL_SAMPLES as f64;
+        let black = ColorGlam::new(0.0, 0.0, 0.0);
+        let (sum, total) = (0..SPECTRAL_SAMPLES)
+            .map(|i| {
+                let wavelength = MIN_WAVELENGTH + (i as f64 + 0.5) * step;
+                let weight = wavelength_weight(wavelength);
+                (
+                    weight * self.reflectance_at(cos_theta, substrate(wavelength), wavelength),
+                    weight,
+                )
+            })
+            .fold((black, black), |(sum, total), (r, w)| (sum + r, total + w));
+        ColorGlam::new(
+            sum.x() / total.x(),
+            sum.y() / total.y(),
+            sum.z() / total.z(),
+        )
+    }
+
+    /// 単一波長での反射率（Airyの式、s偏光とp偏光の平均）
+    ///
+    /// # Arguments
+    ///
+    /// * `cos_theta` - 入射角の余弦
+    /// * `substrate` - 下地の複素屈折率（n, k）
+    /// * `wavelength` - 波長（nm）
+    pub fn reflectance_at(&self, cos_theta: f64, substrate: (f64, f64), wavelength: f64) -> f64 {
+        let cos1 = Complex::real(cos_theta.clamp(0.0, 1.0));
+        let sin2 = Complex::real(1.0 - cos_theta * cos_theta);
+        let n1 = Complex::real(1.0);
+        let n2 = Complex::real(self.ior);
+        let n3 = Complex::new(substrate.0, substrate.1);
+
+        // 膜と下地の中での屈折角の余弦（全反射や吸収では複素数になる）
+        // 虚部が正（減衰する向き）となるよう n・cosθ = √(n² - sin²θ) から求める
+        let cos2 = (n2 * n2 - sin2).sqrt() / n2;
+        let cos3 = (n3 * n3 - sin2).sqrt() / n3;
+
+        // 膜の中を往復する間の位相差
+        let phase = n2 * cos2 * (4.0 * PI * self.thickness / wavelength);
+        let delay = phase.exp_i();
+
+        let airy = |r12: Complex, r23: Complex| {
+            let r = (r12 + r23 * delay) / (Complex::real(1.0) + r12 * r23 * delay);
+            r.norm_sqr().min(1.0)
+        };
+        let rs = airy(
+            (n1 * cos1 - n2 * cos2) / (n1 * cos1 + n2 * cos2),
+            (n2 * cos2 - n3 * cos3) / (n2 * cos2 + n3 * cos3),
+        );
+        let rp = airy(
+            (n2 * cos1 - n1 * cos2) / (n2 * cos1 + n1 * cos2),
+            (n3 * cos2 - n2 * cos3) / (n3 * cos2 + n2 * cos3),
+        );
+        0.5 * (rs + rp)
+    }
+}
+
+/// 干渉の計算に使用する複素数
+#[derive(Debug, Clone, Copy)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    fn real(re: f64) -> Self {
+        Complex { re, im: 0.0 }
+    }
+
+    fn norm_sqr(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+
+    /// 主値の平方根
+    fn sqrt(self) -> Self {
+        let norm = self.norm_sqr().sqrt();
+        let re = (0.5 * (norm + self.re)).max(0.0).sqrt();
+        let im = (0.5 * (norm - self.re)).max(0.0).sqrt();
+        Complex::new(re, if self.im < 0.0 { -im } else { im })
+    }
+
+    /// exp(i・z)
+    fn exp_i(self) -> Self {
+        let scale = (-self.im).exp();
+        Complex::new(scale * self.re.cos(), scale * self.re.sin())
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Mul<f64> for Complex {
+    type Output = Complex;
+    fn mul(self, scale: f64) -> Complex {
+        Complex::new(self.re * scale, self.im * scale)
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+    fn div(self, other: Complex) -> Complex {
+        let denom = other.norm_sqr();
+        Complex::new(
+            (self.re * other.re + self.im * other.im) / denom,
+            (self.im * other.re - self.re * other.im) / denom,
+        )
+    }
+}
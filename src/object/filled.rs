@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use crate::aabb::AABB;
+use crate::ray::Ray;
+use crate::types::{HitRecord, Hittable};
+use crate::volumetric::Medium;
+
+/// 閉じた形状の内部を媒質で満たすラッパー（煙の球、雲、ゼリーなど）
+///
+/// 衝突情報に内部の媒質を記録し、境界を内側へ越えたレイがその媒質を進むようにする
+pub struct FilledObject {
+    object: Arc<dyn Hittable>,
+    medium: Arc<dyn Medium>,
+}
+
+impl FilledObject {
+    /// 新しいラッパーを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - 媒質を囲む閉じた形状
+    /// * `medium` - 内部の媒質
+    pub fn new(object: Arc<dyn Hittable>, medium: Arc<dyn Medium>) -> Self {
+        FilledObject { object, medium }
+    }
+}
+
+impl Hittable for FilledObject {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut rec = self.object.hit(ray, t_min, t_max)?;
+        rec.medium = Some(Arc::clone(&self.medium));
+        Some(rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
+        self.object.bounding_box(time0, time1)
+    }
+}
@@ -41,13 +41,7 @@ impl Hittable for HittableList {
         for object in &self.objects {
             if let Some(hit_record) = object.hit(ray, t_min, closest_so_far) {
                 closest_so_far = hit_record.t;
-                hit_anything = Some(HitRecord {
-                    point: hit_record.point,
-                    normal: hit_record.normal,
-                    material: Arc::clone(&hit_record.material),
-                    t: hit_record.t,
-                    front_face: hit_record.front_face,
-                });
+                hit_anything = Some(hit_record);
             }
         }
 
@@ -1,5 +1,9 @@
+pub mod filled;
 pub mod list;
 pub mod sphere;
+pub mod visibility;
 
+pub use filled::FilledObject;
 pub use list::HittableList;
 pub use sphere::Sphere;
+pub use visibility::{LinkedObject, Visibility};
@@ -1,8 +1,9 @@
+use std::f64::consts::PI;
 use std::sync::Arc;
 
 use crate::aabb::AABB;
 use crate::ray::Ray;
-use crate::types::{HitRecord, Hittable, Material};
+use crate::types::{HitRecord, Hittable, Material, orthonormal_basis};
 use crate::vec3_glam::Vec3Glam;
 
 pub struct Sphere {
@@ -19,6 +20,56 @@ impl Sphere {
             material,
         }
     }
+
+    /// 単位球上の点からテクスチャ座標を計算
+    ///
+    /// uは-X方向から+Z, +X, -Zを経て一周する角度、vは-Yから+Yへの角度を0.0 ~ 1.0に正規化した値
+    fn sphere_uv(p: Vec3Glam) -> (f64, f64) {
+        let theta = (-p.y()).clamp(-1.0, 1.0).acos();
+        let phi = (-p.z()).atan2(p.x()) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
+
+    /// 単位球上の点での位置のテクスチャ座標に対する偏微分を計算
+    ///
+    /// 極ではuに対する偏微分が0になるため、任意の接線を使用する
+    fn sphere_tangents(&self, n: Vec3Glam) -> (Vec3Glam, Vec3Glam) {
+        let dpdu = Vec3Glam::new(n.z(), 0.0, -n.x()) * (2.0 * PI * self.radius);
+        let tangent = if dpdu.length_squared() > 1e-12 {
+            dpdu.unit_vector()
+        } else {
+            orthonormal_basis(n).0
+        };
+        // 経線方向の長さはπr
+        let dpdv = n.cross(&tangent) * (PI * self.radius);
+        (dpdu, dpdv)
+    }
+
+    /// レイのパラメータtでの衝突情報を作成
+    fn hit_record(&self, ray: &Ray, t: f64) -> HitRecord {
+        let point = ray.at(t);
+        let outward_normal = (point - self.center) / self.radius;
+        let (u, v) = Self::sphere_uv(outward_normal);
+        let (dpdu, dpdv) = self.sphere_tangents(outward_normal);
+        let mut rec = HitRecord {
+            point,
+            normal: outward_normal,
+            dpdu,
+            dpdv,
+            material: Arc::clone(&self.material),
+            t,
+            u,
+            v,
+            // 経線方向（長さπr）を基準にフットプリントをテクスチャ座標へ換算
+            footprint: ray.cone_width_at(t) / (PI * self.radius.abs()),
+            front_face: false,
+            light_links: None,
+            medium: None,
+        };
+        rec.set_face_normal(ray, outward_normal);
+
+        rec
+    }
 }
 
 impl Hittable for Sphere {
@@ -33,29 +84,13 @@ impl Hittable for Sphere {
             return None;
         }
 
-        // 衝突点のうち、範囲内で最も近いものを見つける
+        // 衝突点のうち、範囲内でアルファテストを通過する最も近いものを見つける
         let sqrtd = discriminant.sqrt();
-        let mut root = (-half_b - sqrtd) / a;
-        if root < t_min || t_max < root {
-            root = (-half_b + sqrtd) / a;
-            if root < t_min || t_max < root {
-                return None;
-            }
-        }
-
-        let t = root;
-        let point = ray.at(t);
-        let outward_normal = (point - self.center) / self.radius;
-        let mut rec = HitRecord {
-            point,
-            normal: outward_normal,
-            material: Arc::clone(&self.material),
-            t,
-            front_face: false,
-        };
-        rec.set_face_normal(ray, outward_normal);
-
-        Some(rec)
+        [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a]
+            .into_iter()
+            .filter(|&root| t_min <= root && root <= t_max)
+            .map(|root| self.hit_record(ray, root))
+            .find(|rec| self.material.alpha_test(rec))
     }
 
     fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use crate::aabb::AABB;
+use crate::light::LightLinks;
+use crate::ray::{Ray, RayKind};
+use crate::types::{HitRecord, Hittable};
+
+/// レイの種類ごとの可視性
+#[derive(Debug, Clone, Copy)]
+pub struct Visibility {
+    /// カメラから直接見えるか
+    pub camera: bool,
+    /// 影を落とすか
+    pub shadow: bool,
+    /// 反射・屈折などの二次レイから見えるか
+    pub secondary: bool,
+}
+
+impl Visibility {
+    /// 指定した種類のレイから見えるか
+    pub fn allows(&self, kind: RayKind) -> bool {
+        match kind {
+            RayKind::Camera => self.camera,
+            RayKind::Shadow => self.shadow,
+            RayKind::Secondary => self.secondary,
+        }
+    }
+}
+
+impl Default for Visibility {
+    fn default() -> Self {
+        Visibility {
+            camera: true,
+            shadow: true,
+            secondary: true,
+        }
+    }
+}
+
+/// 可視性とライトリンクをオブジェクトに付与するラッパー
+pub struct LinkedObject {
+    object: Arc<dyn Hittable>,
+    visibility: Visibility,
+    light_links: Option<Arc<LightLinks>>,
+}
+
+impl LinkedObject {
+    /// 新しいラッパーを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - 対象のオブジェクト
+    /// * `visibility` - レイの種類ごとの可視性
+    /// * `light_links` - オブジェクトを照らす光源の制限（Noneの場合はすべての光源）
+    pub fn new(
+        object: Arc<dyn Hittable>,
+        visibility: Visibility,
+        light_links: Option<Arc<LightLinks>>,
+    ) -> Self {
+        LinkedObject {
+            object,
+            visibility,
+            light_links,
+        }
+    }
+}
+
+impl Hittable for LinkedObject {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // 見えない種類のレイはオブジェクトを素通りする
+        if !self.visibility.allows(ray.kind()) {
+            return None;
+        }
+
+        let mut rec = self.object.hit(ray, t_min, t_max)?;
+        if let Some(light_links) = &self.light_links {
+            rec.light_links = Some(Arc::clone(light_links));
+        }
+        Some(rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
+        self.object.bounding_box(time0, time1)
+    }
+}
@@ -1,14 +1,88 @@
+use std::sync::Arc;
+
+use crate::spectrum::SampledWavelengths;
 use crate::vec3_glam::Vec3Glam;
+use crate::volumetric::Medium;
+
+/// レイの種類（オブジェクトごとの可視性の判定に使用）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RayKind {
+    /// カメラから直接射出したレイ
+    Camera,
+    /// 光源の遮蔽を調べるシャドウレイ
+    Shadow,
+    /// 反射・屈折などで散乱した二次レイ
+    Secondary,
+}
 
-#[derive(Debug)]
+/// レイが進んでいる空間の媒質
+#[derive(Debug, Clone, Default)]
+pub enum RayMedium {
+    /// 物体の外側（シーン内の媒質があればその中を進む）
+    #[default]
+    Scene,
+    /// 物体内部の媒質
+    Object(Arc<dyn Medium>),
+    /// 媒質を持たない物体の内部（ガラスの中など、シーン内の媒質も存在しない）
+    Vacuum,
+}
+
+#[derive(Debug, Clone)]
 pub struct Ray {
     origin: Vec3Glam,
     direction: Vec3Glam,
+    kind: RayKind,
+    /// レイの原点でのピクセルフットプリントの幅（テクスチャのフィルタリング用）
+    cone_width: f64,
+    /// 単位距離あたりのフットプリントの広がり
+    cone_spread: f64,
+    /// 分散のある物体に当たってから追跡している波長の組（Noneの場合はRGB）
+    wavelengths: Option<SampledWavelengths>,
+    /// レイが進んでいる空間の媒質（表面下散乱、物体内部のボリューム用）
+    medium: RayMedium,
 }
 
 impl Ray {
     pub fn new(origin: Vec3Glam, direction: Vec3Glam) -> Self {
-        Ray { origin, direction }
+        Ray {
+            origin,
+            direction,
+            kind: RayKind::Camera,
+            cone_width: 0.0,
+            cone_spread: 0.0,
+            wavelengths: None,
+            medium: RayMedium::Scene,
+        }
+    }
+
+    /// レイに沿ったピクセルフットプリントの広がりを設定
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - 原点でのフットプリントの幅
+    /// * `spread` - 単位距離あたりの広がり（ピクセルの見込み角）
+    pub fn with_cone(mut self, width: f64, spread: f64) -> Self {
+        self.cone_width = width;
+        self.cone_spread = spread;
+        self
+    }
+
+    /// レイの種類を変更
+    pub fn with_kind(mut self, kind: RayKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// パスが追跡する波長の組を設定
+    pub fn with_wavelengths(mut self, wavelengths: Option<SampledWavelengths>) -> Self {
+        self.wavelengths = wavelengths;
+        self
+    }
+
+    /// レイが内部を進む散乱媒質を設定
+    pub fn with_medium(mut self, medium: RayMedium) -> Self {
+        self.medium = medium;
+        self
     }
 
     pub fn origin(&self) -> Vec3Glam {
@@ -19,6 +93,27 @@ impl Ray {
         self.direction
     }
 
+    pub fn kind(&self) -> RayKind {
+        self.kind
+    }
+
+    pub fn wavelengths(&self) -> Option<&SampledWavelengths> {
+        self.wavelengths.as_ref()
+    }
+
+    pub fn medium(&self) -> &RayMedium {
+        &self.medium
+    }
+
+    pub fn cone_spread(&self) -> f64 {
+        self.cone_spread
+    }
+
+    /// パラメータtの位置でのピクセルフットプリントの幅
+    pub fn cone_width_at(&self, t: f64) -> f64 {
+        self.cone_width + self.cone_spread * t * self.direction.length()
+    }
+
     pub fn at(&self, t: f64) -> Vec3Glam {
         self.origin + self.direction * t
     }
@@ -1,11 +1,51 @@
+use crate::material::{Dispersion, MetalPreset, ThinFilm};
+use crate::object::Visibility;
+use crate::texture::{CheckerSpace, ImageFilter, NoiseBasis, WrapMode};
 use crate::vec3_glam::Vec3Glam;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Scene {
     pub camera: CameraConfig,
     pub objects: Vec<ObjectConfig>,
     pub volumetric: Option<VolumetricConfig>,
+    pub sky: Option<SkyConfig>,
+    pub background: Option<BackgroundConfig>,
+    #[serde(default)]
+    pub lights: Vec<LightConfig>,
+    #[serde(default)]
+    pub light_sampler: LightSamplerConfig,
+    #[serde(default)]
+    pub output: OutputConfig,
+    /// 名前付きマテリアルの定義（オブジェクトから名前で参照する）
+    #[serde(default)]
+    pub materials: HashMap<String, MaterialConfig>,
+    /// 名前付きマテリアルを読み込むライブラリファイル（シーンのmaterialsが優先される）
+    #[serde(default)]
+    pub material_libraries: Vec<String>,
+}
+
+/// マテリアルライブラリのファイル
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MaterialLibraryConfig {
+    pub materials: HashMap<String, MaterialConfig>,
+}
+
+impl MaterialLibraryConfig {
+    pub fn from_yaml_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("マテリアルライブラリを読み込めません: {}: {}", path, e))?;
+        Ok(serde_yaml::from_str(&contents)?)
+    }
+}
+
+/// 出力画像の設定
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OutputConfig {
+    /// 背景を透明にし、アルファチャンネル付きのPNG（output.png）も出力する
+    #[serde(default)]
+    pub alpha: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,7 +75,48 @@ impl From<Vec3Config> for Vec3Glam {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ObjectConfig {
     pub shape: ShapeConfig,
-    pub material: MaterialConfig,
+    /// 表面のマテリアル（内部の媒質のみを指定した場合は見えない境界になる）
+    pub material: Option<MaterialRef>,
+    /// 形状の内部を満たす媒質
+    pub medium: Option<MediumConfig>,
+    #[serde(default)]
+    pub visibility: VisibilityConfig,
+    pub light_links: Option<LightLinksConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VisibilityConfig {
+    pub camera: bool,
+    pub shadow: bool,
+    pub secondary: bool,
+}
+
+impl Default for VisibilityConfig {
+    fn default() -> Self {
+        VisibilityConfig {
+            camera: true,
+            shadow: true,
+            secondary: true,
+        }
+    }
+}
+
+impl From<VisibilityConfig> for Visibility {
+    fn from(v: VisibilityConfig) -> Self {
+        Visibility {
+            camera: v.camera,
+            shadow: v.shadow,
+            secondary: v.secondary,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LightLinksConfig {
+    pub include: Option<Vec<String>>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -44,18 +125,672 @@ pub enum ShapeConfig {
     Sphere { center: Vec3Config, radius: f64 },
 }
 
+/// マテリアルの指定（インラインの定義、または名前付きマテリアルの名前）
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum MaterialRef {
+    Named(String),
+    Inline(Box<MaterialConfig>),
+}
+
+impl<'de> Deserialize<'de> for MaterialRef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // untaggedで読み込むとインライン定義の誤りが曖昧なエラーになるため、文字列かどうかで分岐する
+        match serde_yaml::Value::deserialize(deserializer)? {
+            serde_yaml::Value::String(name) => Ok(MaterialRef::Named(name)),
+            value => serde_yaml::from_value(value)
+                .map(|config| MaterialRef::Inline(Box::new(config)))
+                .map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MaterialConfig {
+    #[serde(flatten)]
+    pub kind: MaterialKindConfig,
+    pub normal_map: Option<NormalMapConfig>,
+    pub bump_map: Option<BumpMapConfig>,
+    pub opacity: Option<OpacityConfig>,
+    /// 布の光沢の層（任意のマテリアルに重ねられる）
+    pub sheen_layer: Option<SheenConfig>,
+}
+
+/// 布の光沢（シーン）の層
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SheenConfig {
+    pub color: ColorConfig,
+    #[serde(default = "default_sheen_roughness")]
+    pub roughness: f64,
+}
+
+fn default_sheen_roughness() -> f64 {
+    0.3
+}
+
+/// 不透明度テクスチャによる切り抜き
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OpacityConfig {
+    /// 輝度を不透明度として使用するテクスチャ
+    pub texture: ColorConfig,
+    #[serde(default)]
+    pub mode: AlphaModeConfig,
+    #[serde(default = "default_alpha_threshold")]
+    pub threshold: f64,
+}
+
+fn default_alpha_threshold() -> f64 {
+    0.5
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub enum AlphaModeConfig {
+    #[default]
+    Threshold,
+    Stochastic,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
-pub enum MaterialConfig {
-    Lambertian { albedo: Vec3Config },
-    Metal { albedo: Vec3Config, fuzz: f64 },
-    Dielectric { ir: f64 },
+pub enum MaterialKindConfig {
+    Lambertian {
+        albedo: ColorConfig,
+    },
+    Metal {
+        /// 垂直入射での反射色（Schlickの近似）
+        albedo: Option<ColorConfig>,
+        #[serde(alias = "fuzz", default)]
+        roughness: f64,
+        /// 代表的な金属の複素屈折率
+        preset: Option<MetalPresetConfig>,
+        /// RGBごとの複素屈折率（etaとkの両方を指定）
+        eta: Option<Vec3Config>,
+        k: Option<Vec3Config>,
+        /// 異方性の強さ（-1.0 ~ 1.0、ヘアライン加工など）
+        #[serde(default)]
+        anisotropy: f64,
+        /// 接線（テクスチャ座標uの方向）からの異方性の向きの回転角（度）
+        #[serde(default)]
+        anisotropy_rotation: f64,
+        /// 表面を覆う酸化膜など
+        thin_film: Option<ThinFilmConfig>,
+    },
+    Dielectric {
+        ir: f64,
+        /// 表面の粗さ（すりガラスや氷など）
+        #[serde(default)]
+        roughness: f64,
+        /// 単位長さあたりの吸収係数（RGBごと）
+        absorption: Option<Vec3Config>,
+        /// 基準の深さでの透過率（absorptionの代わりに指定）
+        transmittance: Option<Vec3Config>,
+        #[serde(default = "default_transmittance_depth")]
+        transmittance_depth: f64,
+        /// 屈折率の波長依存性（指定した場合はirの代わりに使用）
+        dispersion: Option<DispersionConfig>,
+        /// 表面を覆うコーティングなど
+        thin_film: Option<ThinFilmConfig>,
+    },
+    /// 厚みを持たない1枚の面として扱う誘電体の板（窓ガラス、石けん膜など）
+    ThinDielectric {
+        ir: f64,
+        /// 干渉による色を付ける場合の膜厚（nm）
+        film_thickness: Option<f64>,
+    },
+    /// 金属性・粗さなどのパラメータで様々な質感を表すマテリアル
+    Principled {
+        base_color: Option<ColorConfig>,
+        #[serde(default)]
+        metallic: f64,
+        #[serde(default = "default_principled_roughness")]
+        roughness: f64,
+        #[serde(default = "default_principled_specular")]
+        specular: f64,
+        #[serde(default)]
+        clearcoat: f64,
+        #[serde(default = "default_clearcoat_roughness")]
+        clearcoat_roughness: f64,
+        #[serde(default)]
+        sheen: f64,
+        #[serde(default = "default_sheen_tint")]
+        sheen_tint: f64,
+        #[serde(default)]
+        transmission: f64,
+        #[serde(default = "default_principled_ior")]
+        ior: f64,
+        /// 放射色（emission_strengthを掛けて使用）
+        emission: Option<ColorConfig>,
+        #[serde(default = "default_emission_strength")]
+        emission_strength: f64,
+    },
+    /// 2つのマテリアルを重みで確率的に切り替える
+    Mix {
+        first: MaterialRef,
+        second: MaterialRef,
+        /// 2つ目のマテリアルの割合（定数、またはテクスチャの輝度）
+        weight: WeightConfig,
+    },
+    /// 下地のマテリアルに誘電体のクリアコート層を重ねる
+    Coated {
+        base: MaterialRef,
+        #[serde(default = "default_principled_ior")]
+        ir: f64,
+        #[serde(default)]
+        roughness: f64,
+        /// コート層を垂直に1回通過したときの透過率（RGBごと）
+        tint: Option<Vec3Config>,
+    },
+    /// 合成用にアルファを0にして背後を切り抜く
+    Holdout,
+    /// 合成用に影と映り込みのみをアルファに記録する
+    ShadowCatcher {
+        /// 周囲の物体への照り返しに使用する拡散反射色
+        albedo: Option<ColorConfig>,
+        /// 周囲の物体の映り込みの強さ（0.0 ~ 1.0）
+        #[serde(default)]
+        reflection: f64,
+    },
+    /// ランダムウォークによる表面下散乱（albedoとmean_free_path、
+    /// またはabsorptionとscatteringのいずれかの組を指定）
+    Subsurface {
+        #[serde(default = "default_subsurface_ir")]
+        ir: f64,
+        /// 単一散乱アルベド（RGBごと）
+        albedo: Option<Vec3Config>,
+        /// 平均自由行程（RGBごと）
+        mean_free_path: Option<Vec3Config>,
+        /// 単位長さあたりの吸収係数（RGBごと）
+        absorption: Option<Vec3Config>,
+        /// 単位長さあたりの散乱係数（RGBごと）
+        scattering: Option<Vec3Config>,
+    },
+}
+
+fn default_subsurface_ir() -> f64 {
+    1.4
+}
+
+/// 定数の重み、またはテクスチャ
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WeightConfig {
+    Constant(f64),
+    Texture(ColorConfig),
+}
+
+fn default_principled_roughness() -> f64 {
+    0.5
+}
+
+fn default_principled_specular() -> f64 {
+    0.5
+}
+
+fn default_clearcoat_roughness() -> f64 {
+    0.03
+}
+
+fn default_sheen_tint() -> f64 {
+    0.5
+}
+
+fn default_principled_ior() -> f64 {
+    1.5
+}
+
+fn default_emission_strength() -> f64 {
+    1.0
+}
+
+/// 表面を覆う薄膜
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThinFilmConfig {
+    /// 膜厚（nm）
+    pub thickness: f64,
+    /// 膜の屈折率
+    pub ior: f64,
+}
+
+impl From<ThinFilmConfig> for ThinFilm {
+    fn from(f: ThinFilmConfig) -> Self {
+        ThinFilm::new(f.thickness, f.ior)
+    }
+}
+
+fn default_transmittance_depth() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum DispersionConfig {
+    Cauchy { a: f64, b: f64 },
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+    Bk7,
+    Diamond,
+}
+
+impl From<DispersionConfig> for Dispersion {
+    fn from(d: DispersionConfig) -> Self {
+        match d {
+            DispersionConfig::Cauchy { a, b } => Dispersion::Cauchy { a, b },
+            DispersionConfig::Sellmeier { b, c } => Dispersion::Sellmeier { b, c },
+            DispersionConfig::Bk7 => Dispersion::BK7,
+            DispersionConfig::Diamond => Dispersion::DIAMOND,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum MetalPresetConfig {
+    Gold,
+    Copper,
+    Aluminium,
+    Silver,
+}
+
+impl From<MetalPresetConfig> for MetalPreset {
+    fn from(p: MetalPresetConfig) -> Self {
+        match p {
+            MetalPresetConfig::Gold => MetalPreset::Gold,
+            MetalPresetConfig::Copper => MetalPreset::Copper,
+            MetalPresetConfig::Aluminium => MetalPreset::Aluminium,
+            MetalPresetConfig::Silver => MetalPreset::Silver,
+        }
+    }
+}
+
+/// 接空間の法線マップ
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NormalMapConfig {
+    pub path: String,
+    #[serde(default)]
+    pub wrap: WrapModeConfig,
+    #[serde(default = "default_normal_strength")]
+    pub strength: f64,
+    #[serde(default)]
+    pub flip_y: bool,
+}
+
+fn default_normal_strength() -> f64 {
+    1.0
+}
+
+/// 輝度を高さとして扱うバンプマップ
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BumpMapConfig {
+    pub height: ColorConfig,
+    pub strength: f64,
+}
+
+/// 定数の色、またはテクスチャ
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ColorConfig {
+    Constant(Vec3Config),
+    Texture(TextureConfig),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TextureConfig {
+    Solid {
+        color: Vec3Config,
+    },
+    Image {
+        path: String,
+        #[serde(default)]
+        wrap: WrapModeConfig,
+        #[serde(default)]
+        filter: ImageFilterConfig,
+        srgb: Option<bool>,
+        #[serde(default)]
+        channel: ImageChannelConfig,
+    },
+    Checker {
+        even: Vec3Config,
+        odd: Vec3Config,
+        #[serde(default = "default_texture_scale")]
+        scale: f64,
+        #[serde(default)]
+        space: CheckerSpaceConfig,
+    },
+    Noise {
+        low: Vec3Config,
+        high: Vec3Config,
+        #[serde(default = "default_texture_scale")]
+        scale: f64,
+        #[serde(default)]
+        noise: NoiseBasisConfig,
+        #[serde(default = "default_octaves")]
+        octaves: u32,
+        #[serde(default)]
+        turbulence: bool,
+        #[serde(default)]
+        seed: u64,
+    },
+    Marble {
+        base: Vec3Config,
+        vein: Vec3Config,
+        #[serde(default = "default_texture_scale")]
+        scale: f64,
+        #[serde(default)]
+        noise: NoiseBasisConfig,
+        #[serde(default = "default_octaves")]
+        octaves: u32,
+        #[serde(default = "default_marble_distortion")]
+        distortion: f64,
+        #[serde(default)]
+        seed: u64,
+    },
+    Wood {
+        light: Vec3Config,
+        dark: Vec3Config,
+        #[serde(default = "default_texture_scale")]
+        scale: f64,
+        #[serde(default = "default_wood_rings")]
+        rings: f64,
+        #[serde(default)]
+        noise: NoiseBasisConfig,
+        #[serde(default = "default_wood_distortion")]
+        distortion: f64,
+        #[serde(default)]
+        seed: u64,
+    },
+}
+
+fn default_texture_scale() -> f64 {
+    1.0
+}
+
+fn default_octaves() -> u32 {
+    6
+}
+
+fn default_marble_distortion() -> f64 {
+    2.0
+}
+
+fn default_wood_rings() -> f64 {
+    8.0
+}
+
+fn default_wood_distortion() -> f64 {
+    0.1
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub enum CheckerSpaceConfig {
+    Uv,
+    #[default]
+    World,
+}
+
+impl From<CheckerSpaceConfig> for CheckerSpace {
+    fn from(s: CheckerSpaceConfig) -> Self {
+        match s {
+            CheckerSpaceConfig::Uv => CheckerSpace::Uv,
+            CheckerSpaceConfig::World => CheckerSpace::World,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub enum NoiseBasisConfig {
+    #[default]
+    Perlin,
+    Simplex,
+}
+
+impl From<NoiseBasisConfig> for NoiseBasis {
+    fn from(n: NoiseBasisConfig) -> Self {
+        match n {
+            NoiseBasisConfig::Perlin => NoiseBasis::Perlin,
+            NoiseBasisConfig::Simplex => NoiseBasis::Simplex,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub enum WrapModeConfig {
+    #[default]
+    Repeat,
+    Clamp,
+    Mirror,
+}
+
+impl From<WrapModeConfig> for WrapMode {
+    fn from(w: WrapModeConfig) -> Self {
+        match w {
+            WrapModeConfig::Repeat => WrapMode::Repeat,
+            WrapModeConfig::Clamp => WrapMode::Clamp,
+            WrapModeConfig::Mirror => WrapMode::Mirror,
+        }
+    }
+}
+
+/// 画像テクスチャとして使用するチャンネル
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub enum ImageChannelConfig {
+    #[default]
+    Color,
+    /// アルファチャンネルをグレースケールとして使用
+    Alpha,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub enum ImageFilterConfig {
+    Nearest,
+    #[default]
+    Bilinear,
+    Trilinear,
+}
+
+impl From<ImageFilterConfig> for ImageFilter {
+    fn from(f: ImageFilterConfig) -> Self {
+        match f {
+            ImageFilterConfig::Nearest => ImageFilter::Nearest,
+            ImageFilterConfig::Bilinear => ImageFilter::Bilinear,
+            ImageFilterConfig::Trilinear => ImageFilter::Trilinear,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum VolumetricConfig {
-    UniformFog { color: Vec3Config, density: f64 },
+    UniformFog {
+        color: Vec3Config,
+        density: f64,
+    },
+    /// 密度が空間的に変化し、散乱で光が伝わる関与媒質（雲、煙など）
+    Heterogeneous {
+        /// 媒質が存在する範囲（省略した場合はボクセルグリッドの範囲）
+        bounds: Option<BoundsConfig>,
+        density: Box<DensityConfig>,
+        /// 密度1あたりの吸収係数（RGBごと）
+        absorption: Option<Vec3Config>,
+        /// 密度1あたりの散乱係数（RGBごと）
+        scattering: Vec3Config,
+        #[serde(default)]
+        phase: PhaseConfig,
+        emission: Option<EmissionConfig>,
+    },
+}
+
+/// 温度に応じた黒体放射による媒質の発光（炎、爆発など）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmissionConfig {
+    /// 温度場（ケルビン）
+    pub temperature: Box<DensityConfig>,
+    /// 放射の強さの倍率
+    #[serde(default = "default_emission_intensity")]
+    pub intensity: f64,
+}
+
+fn default_emission_intensity() -> f64 {
+    1.0
+}
+
+/// 散乱媒質の位相関数
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum PhaseConfig {
+    /// 等方散乱
+    #[default]
+    Isotropic,
+    /// Henyey–Greenstein（gが正なら前方散乱、負なら後方散乱）
+    HenyeyGreenstein { g: f64 },
+    /// 前方と後方の2つのHenyey–Greensteinローブの混合
+    DoubleHenyeyGreenstein {
+        forward: f64,
+        backward: f64,
+        /// 前方ローブの重み（0.0 ~ 1.0）
+        weight: f64,
+    },
+}
+
+/// 軸に平行なボックスの範囲
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BoundsConfig {
+    pub min: Vec3Config,
+    pub max: Vec3Config,
+}
+
+/// 物体内部の媒質
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MediumConfig {
+    #[serde(default = "default_medium_density")]
+    pub density: MediumDensityConfig,
+    /// 密度1あたりの吸収係数（RGBごと）
+    pub absorption: Option<Vec3Config>,
+    /// 密度1あたりの散乱係数（RGBごと）
+    pub scattering: Vec3Config,
+    #[serde(default)]
+    pub phase: PhaseConfig,
+    /// 発光（密度場を持つ媒質のみ）
+    pub emission: Option<EmissionConfig>,
+}
+
+/// 物体内部の媒質の密度（定数、または空間的に変化する密度場）
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MediumDensityConfig {
+    Constant(f64),
+    Field(DensityConfig),
+}
+
+fn default_medium_density() -> MediumDensityConfig {
+    MediumDensityConfig::Constant(1.0)
+}
+
+/// 関与媒質の密度場
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum DensityConfig {
+    /// フラクタルノイズによる密度
+    Noise {
+        /// 密度の最大値
+        density: f64,
+        #[serde(default = "default_texture_scale")]
+        scale: f64,
+        #[serde(default = "default_octaves")]
+        octaves: u32,
+        /// 密度が0になるノイズ値のしきい値（0.0 ~ 1.0）
+        #[serde(default)]
+        coverage: f64,
+        #[serde(default)]
+        noise: NoiseBasisConfig,
+        #[serde(default)]
+        seed: u64,
+    },
+    /// テクスチャの輝度（0.0 ~ 1.0）に比例する密度（位置で評価するテクスチャを使用）
+    Texture {
+        texture: ColorConfig,
+        /// 輝度1での密度
+        #[serde(default = "default_texture_density")]
+        density: f64,
+    },
+    /// ボクセルグリッドのファイル（Mitsubaのgrid volume形式 .vol）
+    Grid {
+        path: String,
+        /// ボクセル値に掛ける倍率
+        #[serde(default = "default_grid_scale")]
+        scale: f64,
+    },
+}
+
+fn default_grid_scale() -> f64 {
+    1.0
+}
+
+fn default_texture_density() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum SkyConfig {
+    Preetham {
+        sun_elevation: f64,
+        sun_azimuth: f64,
+        turbidity: f64,
+        #[serde(default = "default_sky_intensity")]
+        intensity: f64,
+        #[serde(default = "default_sun_size")]
+        sun_size: f64,
+    },
+}
+
+fn default_sky_intensity() -> f64 {
+    0.05
+}
+
+fn default_sun_size() -> f64 {
+    0.53
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum BackgroundConfig {
+    Solid { color: Vec3Config },
+    Gradient { bottom: Vec3Config, top: Vec3Config },
+    Backplate { image: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LightConfig {
+    Point {
+        name: Option<String>,
+        position: Vec3Config,
+        color: Vec3Config,
+        /// 光の強さ（IES配光を指定した場合は絶対光度への倍率で、省略時は1.0）
+        intensity: Option<f64>,
+        ies: Option<String>,
+        /// IES配光のC0面を配光の軸まわりに回転する角度（度、上から見て反時計回り）
+        ies_rotation: Option<f64>,
+    },
+    Spot {
+        name: Option<String>,
+        position: Vec3Config,
+        direction: Vec3Config,
+        color: Vec3Config,
+        /// 光の強さ（IES配光を指定した場合は絶対光度への倍率で、省略時は1.0）
+        intensity: Option<f64>,
+        cone_angle: f64,
+        falloff_start: Option<f64>,
+        ies: Option<String>,
+        /// IES配光のC0面を配光の軸まわりに回転する角度（度、上から見て反時計回り）
+        ies_rotation: Option<f64>,
+    },
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub enum LightSamplerConfig {
+    Uniform,
+    #[default]
+    Bvh,
 }
 
 impl Scene {
@@ -0,0 +1,242 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::background::Background;
+use crate::types::orthonormal_basis;
+use crate::vec3_glam::{ColorGlam, Vec3Glam};
+
+/// 大気圏外での太陽の輝度（kcd/m²）
+const SUN_LUMINANCE: f64 = 1.6e6;
+
+/// RGB各チャンネルの代表波長（μm）
+const RGB_WAVELENGTHS: [f64; 3] = [0.65, 0.57, 0.475];
+
+/// Perez の輝度分布関数の係数
+#[derive(Clone, Copy)]
+struct PerezCoefficients {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+}
+
+impl PerezCoefficients {
+    /// Perez の輝度分布関数 F(θ, γ)
+    ///
+    /// * `cos_theta` - 視線方向の天頂角の余弦
+    /// * `gamma` - 視線方向と太陽方向のなす角
+    fn evaluate(&self, cos_theta: f64, gamma: f64) -> f64 {
+        let cos_gamma = gamma.cos();
+        (1.0 + self.a * (self.b / cos_theta.max(0.01)).exp())
+            * (1.0 + self.c * (self.d * gamma).exp() + self.e * cos_gamma * cos_gamma)
+    }
+}
+
+/// 太陽方向のサンプリング結果
+pub struct SunSample {
+    /// 太陽へ向かう単位ベクトル
+    pub direction: Vec3Glam,
+    /// 放射輝度をサンプリング確率密度で割った値
+    pub radiance: ColorGlam,
+}
+
+/// Preetham らによる解析的な昼光天空モデル
+///
+/// 太陽の高度・方位角と大気の混濁度（turbidity）から天空の放射輝度を計算する。
+/// 太陽ディスクは光源として重点サンプリングできる。
+pub struct PreethamSky {
+    sun_direction: Vec3Glam,
+    sun_cos_theta: f64,
+    zenith: (f64, f64, f64),
+    perez_y: PerezCoefficients,
+    perez_x: PerezCoefficients,
+    perez_yy: PerezCoefficients,
+    /// 太陽方向での F(0, θs) の値（正規化用）
+    normalization: (f64, f64, f64),
+    sun_radiance: ColorGlam,
+    sun_cos_max: f64,
+    intensity: f64,
+}
+
+impl PreethamSky {
+    /// 新しい天空モデルを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `sun_elevation` - 太陽の高度（度）
+    /// * `sun_azimuth` - 太陽の方位角（度、-Z方向を0として+X方向へ回転）
+    /// * `turbidity` - 大気の混濁度（2.0 ~ 10.0程度）
+    /// * `intensity` - 輝度（kcd/m²）からレンダリング値への換算係数
+    /// * `sun_size` - 太陽の視直径（度）
+    pub fn new(
+        sun_elevation: f64,
+        sun_azimuth: f64,
+        turbidity: f64,
+        intensity: f64,
+        sun_size: f64,
+    ) -> Self {
+        let elevation = sun_elevation.to_radians();
+        let azimuth = sun_azimuth.to_radians();
+        let sun_direction = Vec3Glam::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.cos(),
+        );
+
+        let t = turbidity.clamp(1.7, 10.0);
+        let theta_s = PI / 2.0 - elevation.clamp(0.0, PI / 2.0);
+
+        let perez_y = PerezCoefficients {
+            a: 0.1787 * t - 1.4630,
+            b: -0.3554 * t + 0.4275,
+            c: -0.0227 * t + 5.3251,
+            d: 0.1206 * t - 2.5771,
+            e: -0.0670 * t + 0.3703,
+        };
+        let perez_x = PerezCoefficients {
+            a: -0.0193 * t - 0.2592,
+            b: -0.0665 * t + 0.0008,
+            c: -0.0004 * t + 0.2125,
+            d: -0.0641 * t - 0.8989,
+            e: -0.0033 * t + 0.0452,
+        };
+        let perez_yy = PerezCoefficients {
+            a: -0.0167 * t - 0.2608,
+            b: -0.0950 * t + 0.0092,
+            c: -0.0079 * t + 0.2102,
+            d: -0.0441 * t - 1.6537,
+            e: -0.0109 * t + 0.0529,
+        };
+
+        // 天頂の輝度（kcd/m²）と色度
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let zenith_luminance = ((4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192).max(0.0);
+        let (t2, th, th2, th3) = (t * t, theta_s, theta_s * theta_s, theta_s.powi(3));
+        let zenith_x = t2 * (0.00166 * th3 - 0.00375 * th2 + 0.00209 * th)
+            + t * (-0.02903 * th3 + 0.06377 * th2 - 0.03202 * th + 0.00394)
+            + (0.11693 * th3 - 0.21196 * th2 + 0.06052 * th + 0.25886);
+        let zenith_y = t2 * (0.00275 * th3 - 0.00610 * th2 + 0.00317 * th)
+            + t * (-0.04214 * th3 + 0.08970 * th2 - 0.04153 * th + 0.00516)
+            + (0.15346 * th3 - 0.26756 * th2 + 0.06670 * th + 0.26688);
+
+        let normalization = (
+            perez_y.evaluate(1.0, theta_s),
+            perez_x.evaluate(1.0, theta_s),
+            perez_yy.evaluate(1.0, theta_s),
+        );
+
+        let sun_cos_max = (sun_size.to_radians() / 2.0).cos();
+        let sun_radiance = Self::sun_transmittance(theta_s, t) * (SUN_LUMINANCE * intensity);
+
+        PreethamSky {
+            sun_direction,
+            sun_cos_theta: sun_direction.y(),
+            zenith: (zenith_luminance, zenith_x, zenith_y),
+            perez_y,
+            perez_x,
+            perez_yy,
+            normalization,
+            sun_radiance,
+            sun_cos_max,
+            intensity,
+        }
+    }
+
+    /// 大気による太陽光の透過率（レイリー散乱とエアロゾルによる減衰）
+    fn sun_transmittance(theta_s: f64, turbidity: f64) -> ColorGlam {
+        // Kastenの近似式による相対エアマス
+        let theta_deg = theta_s.to_degrees();
+        let air_mass = 1.0 / (theta_s.cos() + 0.15 * (93.885 - theta_deg).max(1e-3).powf(-1.253));
+
+        // Ångströmの混濁係数
+        let alpha = 1.3;
+        let beta = 0.04608365822050 * turbidity - 0.04586025928522;
+
+        let channel = |lambda: f64| {
+            let rayleigh = (-0.008735 * lambda.powf(-4.08) * air_mass).exp();
+            let aerosol = (-beta * lambda.powf(-alpha) * air_mass).exp();
+            rayleigh * aerosol
+        };
+
+        ColorGlam::new(
+            channel(RGB_WAVELENGTHS[0]),
+            channel(RGB_WAVELENGTHS[1]),
+            channel(RGB_WAVELENGTHS[2]),
+        )
+    }
+
+    /// 指定方向が太陽ディスク内であれば太陽の放射輝度を返す
+    pub fn sun_radiance(&self, direction: Vec3Glam) -> ColorGlam {
+        if self.sun_cos_theta > 0.0
+            && direction.unit_vector().dot(&self.sun_direction) >= self.sun_cos_max
+        {
+            self.sun_radiance
+        } else {
+            ColorGlam::new(0.0, 0.0, 0.0)
+        }
+    }
+
+    /// 太陽ディスク内の方向を一様にサンプリング
+    ///
+    /// 太陽が地平線より下にある場合はNoneを返す
+    pub fn sample_sun(&self) -> Option<SunSample> {
+        if self.sun_cos_theta <= 0.0 {
+            return None;
+        }
+
+        let mut rng = rand::thread_rng();
+        let cos_theta = 1.0 - rng.gen_range(0.0..1.0) * (1.0 - self.sun_cos_max);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = rng.gen_range(0.0..2.0 * PI);
+
+        let (tangent, bitangent) = orthonormal_basis(self.sun_direction);
+        let direction = tangent * (sin_theta * phi.cos())
+            + bitangent * (sin_theta * phi.sin())
+            + self.sun_direction * cos_theta;
+
+        // 立体角 2π(1 - cosθmax) 上の一様サンプリング
+        let solid_angle = 2.0 * PI * (1.0 - self.sun_cos_max);
+
+        Some(SunSample {
+            direction,
+            radiance: self.sun_radiance * solid_angle,
+        })
+    }
+}
+
+impl Background for PreethamSky {
+    /// 指定方向の天空の放射輝度を計算（太陽ディスクは含まない）
+    fn radiance(&self, direction: Vec3Glam) -> ColorGlam {
+        let direction = direction.unit_vector();
+        // 地平線より下は地平線の値を使用
+        let cos_theta = direction.y().max(0.0);
+        let gamma = direction.dot(&self.sun_direction).clamp(-1.0, 1.0).acos();
+
+        let (zenith_luminance, zenith_x, zenith_y) = self.zenith;
+        let luminance =
+            zenith_luminance * self.perez_y.evaluate(cos_theta, gamma) / self.normalization.0;
+        let x = zenith_x * self.perez_x.evaluate(cos_theta, gamma) / self.normalization.1;
+        let y = zenith_y * self.perez_yy.evaluate(cos_theta, gamma) / self.normalization.2;
+
+        xyy_to_rgb(x, y, luminance * self.intensity)
+    }
+}
+
+/// CIE xyY色空間から線形sRGBへ変換
+fn xyy_to_rgb(x: f64, y: f64, luminance: f64) -> ColorGlam {
+    if y <= 0.0 {
+        return ColorGlam::new(0.0, 0.0, 0.0);
+    }
+
+    let cx = x / y * luminance;
+    let cy = luminance;
+    let cz = (1.0 - x - y) / y * luminance;
+
+    ColorGlam::new(
+        (3.2406 * cx - 1.5372 * cy - 0.4986 * cz).max(0.0),
+        (-0.9689 * cx + 1.8758 * cy + 0.0415 * cz).max(0.0),
+        (0.0557 * cx - 0.2040 * cy + 1.0570 * cz).max(0.0),
+    )
+}
@@ -0,0 +1,153 @@
+use std::sync::OnceLock;
+
+use crate::vec3_glam::ColorGlam;
+
+/// サンプリングする可視光の波長の範囲（nm）
+pub const MIN_WAVELENGTH: f64 = 380.0;
+pub const MAX_WAVELENGTH: f64 = 780.0;
+
+/// 可視光の範囲から一様に波長をサンプリング
+///
+/// # Arguments
+///
+/// * `u` - [0, 1)の一様乱数
+pub fn sample_wavelength(u: f64) -> f64 {
+    MIN_WAVELENGTH + u * (MAX_WAVELENGTH - MIN_WAVELENGTH)
+}
+
+/// パスが同時に追跡する波長の数（ヒーロー波長と随伴波長）
+pub const WAVELENGTH_SAMPLES: usize = 4;
+
+/// パスが追跡する波長の組（ヒーロー波長によるスペクトルのサンプリング）
+///
+/// ヒーロー波長を一様にサンプリングし、随伴波長は可視光の範囲を等分した間隔だけ
+/// ずらして置く（範囲の端で折り返す）。反射のように方向が波長によらない散乱では
+/// すべての波長が同じ経路をたどるため、波長ごとの係数を重みに掛けて追跡を続ける。
+/// 分散のある屈折では方向がヒーロー波長で決まり、随伴波長がその方向を選ぶ確率密度は0となる。
+/// この場合のヒーロー波長のMISの重みは1なので、随伴波長を打ち切ってヒーロー波長だけを残す
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampledWavelengths {
+    /// 各波長（nm、先頭がヒーロー波長）
+    wavelengths: [f64; WAVELENGTH_SAMPLES],
+    /// 波長ごとの経路の重み（波長に依存する係数のみを含む）
+    weights: [f64; WAVELENGTH_SAMPLES],
+}
+
+impl SampledWavelengths {
+    /// ヒーロー波長と等間隔の随伴波長をサンプリング
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - [0, 1)の一様乱数（ヒーロー波長の位置）
+    pub fn sample(u: f64) -> Self {
+        SampledWavelengths {
+            wavelengths: std::array::from_fn(|i| {
+                sample_wavelength((u + i as f64 / WAVELENGTH_SAMPLES as f64).fract())
+            }),
+            weights: [1.0; WAVELENGTH_SAMPLES],
+        }
+    }
+
+    /// 方向を決める基準となるヒーロー波長（nm）
+    pub fn hero(&self) -> f64 {
+        self.wavelengths[0]
+    }
+
+    /// 各波長での値を計算
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> [f64; WAVELENGTH_SAMPLES] {
+        self.wavelengths.map(f)
+    }
+
+    /// 波長ごとの係数を重みに掛ける
+    pub fn scaled(mut self, factors: [f64; WAVELENGTH_SAMPLES]) -> Self {
+        for (weight, factor) in self.weights.iter_mut().zip(factors) {
+            *weight *= factor;
+        }
+        self
+    }
+
+    /// 随伴波長を打ち切り、ヒーロー波長だけを追跡する
+    ///
+    /// すべての枠をヒーロー波長で置き換える。RGBの重みは波長の平均なので、
+    /// ヒーロー波長の重みを波長の数倍して1つだけ残すことと同じになる
+    pub fn terminate_secondary(self) -> Self {
+        SampledWavelengths {
+            wavelengths: [self.wavelengths[0]; WAVELENGTH_SAMPLES],
+            weights: [self.weights[0]; WAVELENGTH_SAMPLES],
+        }
+    }
+
+    /// 経路の重みをRGBに投影した値（各波長のRGBの重みの平均）
+    pub fn rgb_weight(&self) -> ColorGlam {
+        self.wavelengths
+            .iter()
+            .zip(self.weights)
+            .map(|(&wavelength, weight)| wavelength_weight(wavelength) * weight)
+            .fold(ColorGlam::new(0.0, 0.0, 0.0), |acc, c| acc + c)
+            / WAVELENGTH_SAMPLES as f64
+    }
+
+    /// 波長ごとの係数を掛けた場合のRGBの重みの、掛けない場合に対する比
+    ///
+    /// 経路の途中で加える寄与（直接光など）の波長依存性をRGBで表すために使う
+    pub fn project(&self, factors: [f64; WAVELENGTH_SAMPLES]) -> ColorGlam {
+        let base = self.rgb_weight();
+        let scaled = self.scaled(factors).rgb_weight();
+        let ratio = |s: f64, b: f64| if b > 0.0 { s / b } else { 0.0 };
+        ColorGlam::new(
+            ratio(scaled.x(), base.x()),
+            ratio(scaled.y(), base.y()),
+            ratio(scaled.z(), base.z()),
+        )
+    }
+}
+
+/// 一様にサンプリングした波長を1つだけ追跡する場合のRGBの重み
+///
+/// 全波長で平均すると白（1.0, 1.0, 1.0）になるよう正規化されている
+pub fn wavelength_weight(wavelength: f64) -> ColorGlam {
+    static NORMALIZATION: OnceLock<ColorGlam> = OnceLock::new();
+    let normalization = NORMALIZATION.get_or_init(|| {
+        let steps = 1000;
+        let sum = (0..steps)
+            .map(|i| wavelength_to_rgb(sample_wavelength((i as f64 + 0.5) / steps as f64)))
+            .fold(ColorGlam::new(0.0, 0.0, 0.0), |acc, c| acc + c);
+        sum / steps as f64
+    });
+
+    let rgb = wavelength_to_rgb(wavelength);
+    ColorGlam::new(
+        rgb.x() / normalization.x(),
+        rgb.y() / normalization.y(),
+        rgb.z() / normalization.z(),
+    )
+}
+
+/// 単色光の色を線形sRGBで計算（色域外の負の成分は0にする）
+fn wavelength_to_rgb(wavelength: f64) -> ColorGlam {
+    let (x, y, z) = cie_xyz(wavelength);
+    ColorGlam::new(
+        (3.2406 * x - 1.5372 * y - 0.4986 * z).max(0.0),
+        (-0.9689 * x + 1.8758 * y + 0.0415 * z).max(0.0),
+        (0.0557 * x - 0.2040 * y + 1.0570 * z).max(0.0),
+    )
+}
+
+/// CIE 1931等色関数の解析的な近似（Wyman et al. 2013）
+fn cie_xyz(wavelength: f64) -> (f64, f64, f64) {
+    // 左右で幅の異なるガウス関数
+    let g = |mu: f64, sigma_left: f64, sigma_right: f64| {
+        let sigma = if wavelength < mu {
+            sigma_left
+        } else {
+            sigma_right
+        };
+        (-0.5 * ((wavelength - mu) / sigma).powi(2)).exp()
+    };
+
+    let x =
+        1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2);
+    let y = 0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1);
+    let z = 1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8);
+    (x, y, z)
+}
@@ -0,0 +1,55 @@
+use super::Texture;
+use crate::vec3_glam::{ColorGlam, Vec3Glam};
+
+/// 市松模様を評価する空間
+#[derive(Debug, Clone, Copy)]
+pub enum CheckerSpace {
+    /// テクスチャ座標上の2次元の市松模様
+    Uv,
+    /// ワールド座標上の3次元の市松模様
+    World,
+}
+
+/// 2色の市松模様のテクスチャ
+pub struct CheckerTexture {
+    even: ColorGlam,
+    odd: ColorGlam,
+    /// 単位長さあたりのマス目の数
+    scale: f64,
+    space: CheckerSpace,
+}
+
+impl CheckerTexture {
+    /// 新しい市松模様のテクスチャを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `even` - 座標の整数部の和が偶数となるマス目の色
+    /// * `odd` - 座標の整数部の和が奇数となるマス目の色
+    /// * `scale` - 単位長さあたりのマス目の数
+    /// * `space` - 市松模様を評価する空間
+    pub fn new(even: ColorGlam, odd: ColorGlam, scale: f64, space: CheckerSpace) -> Self {
+        CheckerTexture {
+            even,
+            odd,
+            scale,
+            space,
+        }
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, point: Vec3Glam) -> ColorGlam {
+        let coords = match self.space {
+            CheckerSpace::Uv => [u, v, 0.0],
+            CheckerSpace::World => [point.x(), point.y(), point.z()],
+        };
+        let cells: i64 = coords.iter().map(|c| (c * self.scale).floor() as i64).sum();
+
+        if cells.rem_euclid(2) == 0 {
+            self.even
+        } else {
+            self.odd
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use super::Texture;
+use crate::image::{ImageData, load_image, load_image_alpha};
+use crate::vec3_glam::{ColorGlam, Vec3Glam};
+
+/// テクスチャ座標が0.0 ~ 1.0の範囲外にある場合の扱い
+#[derive(Debug, Clone, Copy)]
+pub enum WrapMode {
+    /// 繰り返す
+    Repeat,
+    /// 端のピクセルを引き延ばす
+    Clamp,
+    /// 反転しながら繰り返す
+    Mirror,
+}
+
+impl WrapMode {
+    /// ピクセル座標を画像の範囲内に収める
+    fn apply(self, index: i64, size: u32) -> u32 {
+        let size = size as i64;
+        let wrapped = match self {
+            WrapMode::Repeat => index.rem_euclid(size),
+            WrapMode::Clamp => index.clamp(0, size - 1),
+            WrapMode::Mirror => {
+                let period = index.rem_euclid(2 * size);
+                if period < size {
+                    period
+                } else {
+                    2 * size - 1 - period
+                }
+            }
+        };
+        wrapped as u32
+    }
+}
+
+/// テクスチャの補間方法
+#[derive(Debug, Clone, Copy)]
+pub enum ImageFilter {
+    /// 最も近いピクセルを使用
+    Nearest,
+    /// 周囲4ピクセルを線形補間
+    Bilinear,
+    /// ミップマップの2つのレベルをそれぞれ双線形補間し、さらに線形補間
+    Trilinear,
+}
+
+/// 画像ファイルから読み込んだテクスチャ
+pub struct ImageTexture {
+    /// ミップマップ（0番目が元の解像度）
+    levels: Vec<ImageData>,
+    wrap: WrapMode,
+    filter: ImageFilter,
+}
+
+impl ImageTexture {
+    /// 新しい画像テクスチャを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - 線形色空間の画像
+    /// * `wrap` - 範囲外のテクスチャ座標の扱い
+    /// * `filter` - 補間方法
+    pub fn new(image: ImageData, wrap: WrapMode, filter: ImageFilter) -> Self {
+        let mut levels = vec![image];
+        if let ImageFilter::Trilinear = filter {
+            while let Some(next) = Self::downsample(levels.last().unwrap()) {
+                levels.push(next);
+            }
+        }
+
+        ImageTexture {
+            levels,
+            wrap,
+            filter,
+        }
+    }
+
+    /// 画像ファイルからテクスチャを読み込む
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 画像ファイルのパス（PNG/JPEG/PPM/HDR）
+    /// * `wrap` - 範囲外のテクスチャ座標の扱い
+    /// * `filter` - 補間方法
+    /// * `srgb` - sRGBとして線形色空間へ変換するか（Noneの場合はHDR以外を変換）
+    pub fn load(
+        path: &str,
+        wrap: WrapMode,
+        filter: ImageFilter,
+        srgb: Option<bool>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let image = load_image(path)?;
+        let srgb = srgb.unwrap_or(!path.to_lowercase().ends_with(".hdr"));
+
+        let image = if srgb {
+            let pixels = (0..image.height())
+                .flat_map(|y| (0..image.width()).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    let p = image.pixel(x, y);
+                    ColorGlam::new(
+                        srgb_to_linear(p.x()),
+                        srgb_to_linear(p.y()),
+                        srgb_to_linear(p.z()),
+                    )
+                })
+                .collect();
+            ImageData::new(image.width(), image.height(), pixels)
+        } else {
+            image
+        };
+
+        Ok(Self::new(image, wrap, filter))
+    }
+
+    /// 画像ファイルのアルファチャンネルをグレースケールのテクスチャとして読み込む
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 画像ファイルのパス
+    /// * `wrap` - 範囲外のテクスチャ座標の扱い
+    /// * `filter` - 補間方法
+    pub fn load_alpha(
+        path: &str,
+        wrap: WrapMode,
+        filter: ImageFilter,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::new(load_image_alpha(path)?, wrap, filter))
+    }
+
+    /// 2x2ピクセルの平均で半分の解像度の画像を生成（1x1の場合はNone）
+    fn downsample(image: &ImageData) -> Option<ImageData> {
+        if image.width() == 1 && image.height() == 1 {
+            return None;
+        }
+
+        let width = (image.width() / 2).max(1);
+        let height = (image.height() / 2).max(1);
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let x0 = (2 * x).min(image.width() - 1);
+                let x1 = (2 * x + 1).min(image.width() - 1);
+                let y0 = (2 * y).min(image.height() - 1);
+                let y1 = (2 * y + 1).min(image.height() - 1);
+                let sum = image.pixel(x0, y0)
+                    + image.pixel(x1, y0)
+                    + image.pixel(x0, y1)
+                    + image.pixel(x1, y1);
+                pixels.push(sum * 0.25);
+            }
+        }
+
+        Some(ImageData::new(width, height, pixels))
+    }
+
+    /// テクスチャ座標をピクセル座標に変換（vは下端が0）
+    fn texel_coords(image: &ImageData, u: f64, v: f64) -> (f64, f64) {
+        (
+            u * image.width() as f64 - 0.5,
+            (1.0 - v) * image.height() as f64 - 0.5,
+        )
+    }
+
+    fn texel(&self, image: &ImageData, x: i64, y: i64) -> ColorGlam {
+        image.pixel(
+            self.wrap.apply(x, image.width()),
+            self.wrap.apply(y, image.height()),
+        )
+    }
+
+    fn nearest(&self, image: &ImageData, u: f64, v: f64) -> ColorGlam {
+        let (x, y) = Self::texel_coords(image, u, v);
+        self.texel(image, x.round() as i64, y.round() as i64)
+    }
+
+    fn bilinear(&self, image: &ImageData, u: f64, v: f64) -> ColorGlam {
+        let (x, y) = Self::texel_coords(image, u, v);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.texel(image, x0, y0) * (1.0 - tx) + self.texel(image, x0 + 1, y0) * tx;
+        let bottom =
+            self.texel(image, x0, y0 + 1) * (1.0 - tx) + self.texel(image, x0 + 1, y0 + 1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+
+    fn trilinear(&self, u: f64, v: f64, footprint: f64) -> ColorGlam {
+        // フットプリントが何テクセル分かからミップマップのレベルを決める
+        let base = &self.levels[0];
+        let texels = footprint * base.width().max(base.height()) as f64;
+        let max_level = (self.levels.len() - 1) as f64;
+        let level = if texels > 1.0 {
+            texels.log2().min(max_level)
+        } else {
+            0.0
+        };
+
+        let lower = level.floor() as usize;
+        let upper = (lower + 1).min(self.levels.len() - 1);
+        let t = level - lower as f64;
+
+        let color = self.bilinear(&self.levels[lower], u, v);
+        if t > 0.0 {
+            color * (1.0 - t) + self.bilinear(&self.levels[upper], u, v) * t
+        } else {
+            color
+        }
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, point: Vec3Glam) -> ColorGlam {
+        self.value_filtered(u, v, point, 0.0)
+    }
+
+    fn value_filtered(&self, u: f64, v: f64, _point: Vec3Glam, footprint: f64) -> ColorGlam {
+        match self.filter {
+            ImageFilter::Nearest => self.nearest(&self.levels[0], u, v),
+            ImageFilter::Bilinear => self.bilinear(&self.levels[0], u, v),
+            ImageFilter::Trilinear => self.trilinear(u, v, footprint),
+        }
+    }
+}
+
+/// sRGBの値を線形色空間の値に変換
+fn srgb_to_linear(value: f64) -> f64 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
@@ -0,0 +1,57 @@
+use crate::types::HitRecord;
+use crate::vec3_glam::{ColorGlam, Vec3Glam};
+
+pub mod checker;
+pub mod image;
+pub mod noise;
+pub mod procedural;
+
+pub use checker::{CheckerSpace, CheckerTexture};
+pub use image::{ImageFilter, ImageTexture, WrapMode};
+pub use noise::{Noise, NoiseBasis};
+pub use procedural::{MarbleTexture, NoiseTexture, WoodTexture};
+
+/// 表面上の位置に応じた色を返すテクスチャのトレイト
+pub trait Texture: Send + Sync {
+    /// 指定位置の色を取得
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - テクスチャ座標のu成分（0.0 ~ 1.0）
+    /// * `v` - テクスチャ座標のv成分（0.0 ~ 1.0）
+    /// * `point` - 衝突点のワールド座標
+    fn value(&self, u: f64, v: f64, point: Vec3Glam) -> ColorGlam;
+
+    /// フィルタ幅を考慮して色を取得
+    ///
+    /// フィルタリングを行わないテクスチャは`value`と同じ値を返す
+    ///
+    /// # Arguments
+    ///
+    /// * `footprint` - テクスチャ座標空間でのフィルタ幅
+    fn value_filtered(&self, u: f64, v: f64, point: Vec3Glam, _footprint: f64) -> ColorGlam {
+        self.value(u, v, point)
+    }
+
+    /// 衝突情報からテクスチャの色を取得
+    fn value_at(&self, rec: &HitRecord) -> ColorGlam {
+        self.value_filtered(rec.u, rec.v, rec.point, rec.footprint)
+    }
+}
+
+/// 一様な色のテクスチャ
+pub struct SolidColor {
+    color: ColorGlam,
+}
+
+impl SolidColor {
+    pub fn new(color: ColorGlam) -> Self {
+        SolidColor { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _point: Vec3Glam) -> ColorGlam {
+        self.color
+    }
+}
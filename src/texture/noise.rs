@@ -0,0 +1,193 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::vec3_glam::Vec3Glam;
+
+/// ノイズの基底関数の種類
+#[derive(Debug, Clone, Copy)]
+pub enum NoiseBasis {
+    /// 格子上の勾配を補間するPerlinノイズ
+    Perlin,
+    /// 単体（四面体）格子上で評価するシンプレックスノイズ
+    Simplex,
+}
+
+/// シード値から生成した順列表を用いる3次元の勾配ノイズ
+///
+/// 出力はおおよそ-1.0 ~ 1.0の範囲
+pub struct Noise {
+    basis: NoiseBasis,
+    /// 0 ~ 255の順列を2回繰り返したもの
+    permutation: Vec<u8>,
+}
+
+impl Noise {
+    /// 新しいノイズ関数を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `basis` - 基底関数の種類
+    /// * `seed` - 順列表を生成するためのシード値
+    pub fn new(basis: NoiseBasis, seed: u64) -> Self {
+        let mut table: Vec<u8> = (0..=255).collect();
+        table.shuffle(&mut StdRng::seed_from_u64(seed));
+        let permutation = table.iter().chain(table.iter()).copied().collect();
+
+        Noise { basis, permutation }
+    }
+
+    /// 指定位置のノイズ値を計算
+    pub fn value(&self, point: Vec3Glam) -> f64 {
+        match self.basis {
+            NoiseBasis::Perlin => self.perlin(point.x(), point.y(), point.z()),
+            NoiseBasis::Simplex => self.simplex(point.x(), point.y(), point.z()),
+        }
+    }
+
+    /// 複数オクターブを重ねたフラクタルノイズ（fBm）
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - 評価する位置
+    /// * `octaves` - 重ねるオクターブ数
+    pub fn fbm(&self, point: Vec3Glam, octaves: u32) -> f64 {
+        self.accumulate(point, octaves, |n| n)
+    }
+
+    /// 各オクターブの絶対値を重ねた乱流ノイズ（0.0以上の値）
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - 評価する位置
+    /// * `octaves` - 重ねるオクターブ数
+    pub fn turbulence(&self, point: Vec3Glam, octaves: u32) -> f64 {
+        self.accumulate(point, octaves, f64::abs)
+    }
+
+    /// 周波数を2倍、振幅を半分にしながらオクターブを重ねる
+    fn accumulate(&self, point: Vec3Glam, octaves: u32, shape: impl Fn(f64) -> f64) -> f64 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        for _ in 0..octaves.max(1) {
+            sum += amplitude * shape(self.value(point * frequency));
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        sum
+    }
+
+    fn hash(&self, x: i64, y: i64, z: i64) -> u8 {
+        let p = &self.permutation;
+        let x = (x & 255) as usize;
+        let y = (y & 255) as usize;
+        let z = (z & 255) as usize;
+        p[p[p[x] as usize + y] as usize + z]
+    }
+
+    /// ハッシュ値から選んだ12方向の勾配ベクトルとの内積
+    fn gradient(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+        let h = hash & 15;
+        let u = if h < 8 { x } else { y };
+        let v = if h < 4 {
+            y
+        } else if h == 12 || h == 14 {
+            x
+        } else {
+            z
+        };
+        (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+    }
+
+    /// 改良版Perlinノイズ
+    fn perlin(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (xi, yi, zi) = (x.floor() as i64, y.floor() as i64, z.floor() as i64);
+        let (xf, yf, zf) = (x - x.floor(), y - y.floor(), z - z.floor());
+
+        // 5次のエルミート補間で格子点での2階微分を連続にする
+        let fade = |t: f64| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let (u, v, w) = (fade(xf), fade(yf), fade(zf));
+        let lerp = |t: f64, a: f64, b: f64| a + t * (b - a);
+
+        let corner = |dx: i64, dy: i64, dz: i64| {
+            Self::gradient(
+                self.hash(xi + dx, yi + dy, zi + dz),
+                xf - dx as f64,
+                yf - dy as f64,
+                zf - dz as f64,
+            )
+        };
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, corner(0, 0, 0), corner(1, 0, 0)),
+                lerp(u, corner(0, 1, 0), corner(1, 1, 0)),
+            ),
+            lerp(
+                v,
+                lerp(u, corner(0, 0, 1), corner(1, 0, 1)),
+                lerp(u, corner(0, 1, 1), corner(1, 1, 1)),
+            ),
+        )
+    }
+
+    /// 3次元シンプレックスノイズ
+    fn simplex(&self, x: f64, y: f64, z: f64) -> f64 {
+        const SKEW: f64 = 1.0 / 3.0;
+        const UNSKEW: f64 = 1.0 / 6.0;
+
+        // 空間を歪めて、点を含む単体の原点を求める
+        let s = (x + y + z) * SKEW;
+        let (i, j, k) = (
+            (x + s).floor() as i64,
+            (y + s).floor() as i64,
+            (z + s).floor() as i64,
+        );
+        let t = (i + j + k) as f64 * UNSKEW;
+        let x0 = x - (i as f64 - t);
+        let y0 = y - (j as f64 - t);
+        let z0 = z - (k as f64 - t);
+
+        // 立方体を分割する6つの四面体のうち、点を含むものを選ぶ
+        let ((i1, j1, k1), (i2, j2, k2)) = if x0 >= y0 {
+            if y0 >= z0 {
+                ((1, 0, 0), (1, 1, 0))
+            } else if x0 >= z0 {
+                ((1, 0, 0), (1, 0, 1))
+            } else {
+                ((0, 0, 1), (1, 0, 1))
+            }
+        } else if y0 < z0 {
+            ((0, 0, 1), (0, 1, 1))
+        } else if x0 < z0 {
+            ((0, 1, 0), (0, 1, 1))
+        } else {
+            ((0, 1, 0), (1, 1, 0))
+        };
+
+        let corners = [(0, 0, 0), (i1, j1, k1), (i2, j2, k2), (1, 1, 1)];
+        let sum: f64 = corners
+            .iter()
+            .enumerate()
+            .map(|(n, &(di, dj, dk))| {
+                let offset = n as f64 * UNSKEW;
+                let dx = x0 - di as f64 + offset;
+                let dy = y0 - dj as f64 + offset;
+                let dz = z0 - dk as f64 + offset;
+                let falloff = 0.6 - dx * dx - dy * dy - dz * dz;
+                if falloff <= 0.0 {
+                    0.0
+                } else {
+                    let hash = self.hash(i + di, j + dj, k + dk);
+                    falloff.powi(4) * Self::gradient(hash, dx, dy, dz)
+                }
+            })
+            .sum();
+
+        // 出力がおおよそ-1.0 ~ 1.0になるようスケーリング
+        32.0 * sum
+    }
+}
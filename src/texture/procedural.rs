@@ -0,0 +1,166 @@
+use super::Texture;
+use super::noise::Noise;
+use crate::vec3_glam::{ColorGlam, Vec3Glam};
+
+/// 2色をノイズ値で補間するテクスチャ
+pub struct NoiseTexture {
+    noise: Noise,
+    low: ColorGlam,
+    high: ColorGlam,
+    scale: f64,
+    octaves: u32,
+    /// fBmの代わりに乱流ノイズを使用するか
+    turbulence: bool,
+}
+
+impl NoiseTexture {
+    /// 新しいノイズテクスチャを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `noise` - ノイズ関数
+    /// * `low` - ノイズ値が最小のときの色
+    /// * `high` - ノイズ値が最大のときの色
+    /// * `scale` - ワールド座標に掛ける周波数
+    /// * `octaves` - 重ねるオクターブ数
+    /// * `turbulence` - fBmの代わりに乱流ノイズを使用するか
+    pub fn new(
+        noise: Noise,
+        low: ColorGlam,
+        high: ColorGlam,
+        scale: f64,
+        octaves: u32,
+        turbulence: bool,
+    ) -> Self {
+        NoiseTexture {
+            noise,
+            low,
+            high,
+            scale,
+            octaves,
+            turbulence,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, point: Vec3Glam) -> ColorGlam {
+        let p = point * self.scale;
+        let t = if self.turbulence {
+            self.noise.turbulence(p, self.octaves)
+        } else {
+            0.5 * (self.noise.fbm(p, self.octaves) + 1.0)
+        };
+        mix(self.low, self.high, t)
+    }
+}
+
+/// 乱流で揺らいだx軸方向の縞模様による大理石のテクスチャ
+pub struct MarbleTexture {
+    noise: Noise,
+    base: ColorGlam,
+    vein: ColorGlam,
+    scale: f64,
+    octaves: u32,
+    /// 縞模様を揺らす乱流の強さ
+    distortion: f64,
+}
+
+impl MarbleTexture {
+    /// 新しい大理石のテクスチャを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `noise` - ノイズ関数
+    /// * `base` - 地の色
+    /// * `vein` - 筋の色
+    /// * `scale` - ワールド座標に掛ける周波数
+    /// * `octaves` - 乱流のオクターブ数
+    /// * `distortion` - 縞模様を揺らす乱流の強さ
+    pub fn new(
+        noise: Noise,
+        base: ColorGlam,
+        vein: ColorGlam,
+        scale: f64,
+        octaves: u32,
+        distortion: f64,
+    ) -> Self {
+        MarbleTexture {
+            noise,
+            base,
+            vein,
+            scale,
+            octaves,
+            distortion,
+        }
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _u: f64, _v: f64, point: Vec3Glam) -> ColorGlam {
+        let p = point * self.scale;
+        let phase = p.x() + self.distortion * self.noise.turbulence(p, self.octaves);
+        // 正弦波の谷付近だけを筋にする
+        let t = (1.0 - (0.5 * (1.0 + phase.sin()))).powi(3);
+        mix(self.base, self.vein, t)
+    }
+}
+
+/// y軸を中心とした年輪模様の木目テクスチャ
+pub struct WoodTexture {
+    noise: Noise,
+    light: ColorGlam,
+    dark: ColorGlam,
+    scale: f64,
+    /// 単位長さあたりの年輪の数
+    rings: f64,
+    /// 年輪を揺らすノイズの強さ
+    distortion: f64,
+}
+
+impl WoodTexture {
+    /// 新しい木目のテクスチャを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `noise` - ノイズ関数
+    /// * `light` - 早材（明るい部分）の色
+    /// * `dark` - 晩材（暗い部分）の色
+    /// * `scale` - ワールド座標に掛ける周波数
+    /// * `rings` - 単位長さあたりの年輪の数
+    /// * `distortion` - 年輪を揺らすノイズの強さ
+    pub fn new(
+        noise: Noise,
+        light: ColorGlam,
+        dark: ColorGlam,
+        scale: f64,
+        rings: f64,
+        distortion: f64,
+    ) -> Self {
+        WoodTexture {
+            noise,
+            light,
+            dark,
+            scale,
+            rings,
+            distortion,
+        }
+    }
+}
+
+impl Texture for WoodTexture {
+    fn value(&self, _u: f64, _v: f64, point: Vec3Glam) -> ColorGlam {
+        let p = point * self.scale;
+        let radius = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        let ring = (radius + self.distortion * self.noise.fbm(p, 4)) * self.rings;
+        // 年輪の中で明るい部分から暗い部分へ徐々に変化させる
+        let t = ring.rem_euclid(1.0).powi(2);
+        mix(self.light, self.dark, t)
+    }
+}
+
+/// 2色を線形補間
+fn mix(a: ColorGlam, b: ColorGlam, t: f64) -> ColorGlam {
+    let t = t.clamp(0.0, 1.0);
+    a * (1.0 - t) + b * t
+}
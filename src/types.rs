@@ -1,6 +1,8 @@
 use crate::aabb::AABB;
+use crate::light::LightLinks;
 use crate::ray::Ray;
 use crate::vec3_glam::Vec3Glam;
+use crate::volumetric::Medium;
 use std::sync::Arc;
 
 type ColorGlam = Vec3Glam;
@@ -8,14 +10,30 @@ type ColorGlam = Vec3Glam;
 pub struct ScatterInfo {
     pub attenuation: ColorGlam,
     pub scattered: Ray,
+    /// 散乱方向が直接光サンプリングでカバーされない（鏡面的な）ローブから選ばれたか
+    pub specular: bool,
 }
 
+#[derive(Clone)]
 pub struct HitRecord {
     pub point: Vec3Glam,
     pub normal: Vec3Glam,
+    /// テクスチャ座標uに対する位置の偏微分（接線方向）
+    pub dpdu: Vec3Glam,
+    /// テクスチャ座標vに対する位置の偏微分（従接線方向）
+    pub dpdv: Vec3Glam,
     pub material: Arc<dyn Material>,
     pub t: f64,
+    /// テクスチャ座標
+    pub u: f64,
+    pub v: f64,
+    /// テクスチャ座標空間でのピクセルフットプリントの幅
+    pub footprint: f64,
     pub front_face: bool,
+    /// このオブジェクトを照らす光源の制限（Noneの場合はすべての光源）
+    pub light_links: Option<Arc<LightLinks>>,
+    /// オブジェクト内部の媒質（境界を内側へ越えたレイが進む媒質）
+    pub medium: Option<Arc<dyn Medium>>,
 }
 
 impl HitRecord {
@@ -29,8 +47,72 @@ impl HitRecord {
     }
 }
 
+/// 合成用のマット（カメラから直接見えた場合の特別な扱い）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Matte {
+    /// アルファを0にして背後を切り抜く
+    Holdout,
+    /// 表面に落ちた影と映り込みのみをアルファに記録する
+    ShadowCatcher {
+        /// 映り込みの強さ（0.0 ~ 1.0）
+        reflection: f64,
+    },
+}
+
 pub trait Material: Send + Sync + 'static {
     fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterInfo>;
+
+    /// 直接光サンプリング用にBSDFとコサイン項の積を評価
+    ///
+    /// 鏡面反射など方向を指定して評価できないマテリアルはNoneを返す
+    ///
+    /// # Arguments
+    ///
+    /// * `ray_in` - 入射レイ
+    /// * `rec` - 衝突情報
+    /// * `direction` - 光源へ向かう単位ベクトル
+    fn eval(&self, _ray_in: &Ray, _rec: &HitRecord, _direction: Vec3Glam) -> Option<ColorGlam> {
+        None
+    }
+
+    /// 表面から放射される光
+    fn emitted(&self, _rec: &HitRecord) -> ColorGlam {
+        ColorGlam::new(0.0, 0.0, 0.0)
+    }
+
+    /// 衝突を有効とするかどうかを判定（アルファマスクによる切り抜き用）
+    ///
+    /// falseを返した場合、交差判定はその衝突を無視して奥の衝突を探す
+    fn alpha_test(&self, _rec: &HitRecord) -> bool {
+        true
+    }
+
+    /// 合成用のマットとして扱う場合はその種類を返す
+    fn matte(&self, _rec: &HitRecord) -> Option<Matte> {
+        None
+    }
+
+    /// 媒質の境界を示すだけで光をそのまま通す表面か
+    ///
+    /// trueの場合、シャドウレイは表面を通過し、内部の媒質による減衰のみを受ける
+    fn is_interface(&self, _rec: &HitRecord) -> bool {
+        false
+    }
+
+    /// 厚みを持たない面か（薄い板など）
+    ///
+    /// trueの場合、透過したレイは物体の内部に入らず、入射側と同じ媒質を進む
+    fn is_thin(&self, _rec: &HitRecord) -> bool {
+        false
+    }
+
+    /// シャドウレイが方向directionへ表面を通過するときの透過率
+    ///
+    /// Noneの場合は光を遮る。媒質の境界や薄い板のように、光の方向を変えずに
+    /// 透過させる表面のみが値を返す
+    fn shadow_transmittance(&self, _rec: &HitRecord, _direction: Vec3Glam) -> Option<ColorGlam> {
+        None
+    }
 }
 
 pub trait Hittable: Send + Sync {
@@ -66,3 +148,15 @@ pub fn random_unit_vector() -> Vec3Glam {
 
     Vec3Glam::new(r * a.cos(), r * a.sin(), z)
 }
+
+/// 法線に直交する2つの単位ベクトルを生成
+pub fn orthonormal_basis(normal: Vec3Glam) -> (Vec3Glam, Vec3Glam) {
+    let a = if normal.x().abs() > 0.9 {
+        Vec3Glam::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3Glam::new(1.0, 0.0, 0.0)
+    };
+    let tangent = normal.cross(&a).unit_vector();
+    let bitangent = normal.cross(&tangent);
+    (tangent, bitangent)
+}
@@ -123,11 +123,16 @@ impl Neg for Vec3Glam {
 pub type ColorGlam = Vec3Glam;
 
 impl ColorGlam {
-    pub fn to_rgb(&self) -> (u8, u8, u8) {
+    pub fn to_rgb(self) -> (u8, u8, u8) {
         (
             (256.0 * self.x().clamp(0.0, 0.999)) as u8,
             (256.0 * self.y().clamp(0.0, 0.999)) as u8,
             (256.0 * self.z().clamp(0.0, 0.999)) as u8,
         )
     }
+
+    /// 相対輝度（Rec. 709の係数）
+    pub fn luminance(self) -> f64 {
+        0.2126 * self.x() + 0.7152 * self.y() + 0.0722 * self.z()
+    }
 }
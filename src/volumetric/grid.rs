@@ -0,0 +1,380 @@
+use super::heterogeneous::{DensityField, MajorantSegment};
+use crate::aabb::AABB;
+use crate::ray::Ray;
+use crate::vec3_glam::Vec3Glam;
+
+/// ブリックの1辺のボクセル数
+const BRICK_SIZE: usize = 8;
+
+/// 疎なボクセルグリッド（煙や爆発などのシミュレーション結果）
+///
+/// Mitsubaのgrid volume形式（.vol）を読み込む。ボクセルは境界ボックスの角と
+/// 各軸を等分した位置にあり、その間は三線形補間する。
+/// ボクセルは8x8x8のブリック単位で保持し、すべて0のブリックは確保しない。
+/// ブリックごとの最大値を優関数のグリッドとし、レイが通るブリックごとに
+/// 優関数を切り替えて、空の領域や薄い領域での空衝突を減らす
+pub struct VoxelGrid {
+    /// 各軸のボクセル数
+    resolution: [usize; 3],
+    /// グリッドが存在する範囲
+    bounds: AABB,
+    /// 各軸のブリック数
+    bricks: [usize; 3],
+    /// ブリックごとのボクセル値（すべて0のブリックはNone）
+    data: Vec<Option<Box<[f32]>>>,
+    /// ブリックの範囲の補間に使うボクセルの最大値（優関数のグリッド）
+    majorants: Vec<f64>,
+    /// 値に掛ける倍率
+    scale: f64,
+}
+
+impl VoxelGrid {
+    /// .volファイルを読み込む
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - ファイルのパス
+    /// * `scale` - ボクセル値に掛ける倍率
+    pub fn from_file(path: &str, scale: f64) -> Result<Self, Box<dyn std::error::Error>> {
+        let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&bytes, scale).map_err(|e| format!("{}: {}", path, e).into())
+    }
+
+    /// .vol形式のバイト列を解析
+    ///
+    /// リトルエンディアンで、以下の順に並ぶ（複数チャンネルの場合は最初のチャンネルを使用）
+    ///
+    /// * `"VOL"` と バージョン3（1バイト）
+    /// * エンコーディング（i32、1: float32、3: uint8を0.0 ~ 1.0に正規化）
+    /// * x, y, zのボクセル数とチャンネル数（i32）
+    /// * 境界ボックスの最小点と最大点（f32 x 6）
+    /// * ボクセル値（xが最も速く変わり、次にy、z）
+    pub fn parse(bytes: &[u8], scale: f64) -> Result<Self, String> {
+        if bytes.len() < 48 || &bytes[0..3] != b"VOL" {
+            return Err("VOL形式のヘッダーが見つかりません".to_string());
+        }
+        if bytes[3] != 3 {
+            return Err(format!("未対応のバージョンです: {}", bytes[3]));
+        }
+        let int = |offset: usize| i32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        let float =
+            |offset: usize| f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+
+        let encoding = int(4);
+        let value_size = match encoding {
+            1 => 4,
+            3 => 1,
+            _ => return Err(format!("未対応のエンコーディングです: {}", encoding)),
+        };
+        let dims = [int(8), int(12), int(16)];
+        let channels = int(20);
+        if dims.iter().any(|&n| n < 1) || channels < 1 {
+            return Err("ボクセル数とチャンネル数は1以上である必要があります".to_string());
+        }
+        let resolution = dims.map(|n| n as usize);
+        let channels = channels as usize;
+        let bounds = AABB::new(
+            Vec3Glam::new(float(24) as f64, float(28) as f64, float(32) as f64),
+            Vec3Glam::new(float(36) as f64, float(40) as f64, float(44) as f64),
+        );
+
+        // 壊れたヘッダーで巨大な値が入っていても桁あふれしないよう検査する
+        let size = resolution
+            .iter()
+            .try_fold(1usize, |acc, &n| acc.checked_mul(n))
+            .and_then(|count| count.checked_mul(channels))
+            .and_then(|values| values.checked_mul(value_size))
+            .ok_or_else(|| {
+                format!(
+                    "ボクセルデータが大きすぎます（{}x{}x{}、{}チャンネル）",
+                    resolution[0], resolution[1], resolution[2], channels
+                )
+            })?;
+        let data = &bytes[48..];
+        if data.len() < size {
+            return Err(format!(
+                "ボクセルデータが不足しています（{}x{}x{}、{}チャンネル）",
+                resolution[0], resolution[1], resolution[2], channels
+            ));
+        }
+        let value = |index: usize| -> f32 {
+            let offset = index * channels * value_size;
+            match encoding {
+                1 => f32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()),
+                _ => data[offset] as f32 / 255.0,
+            }
+        };
+
+        Ok(Self::from_dense(
+            resolution,
+            bounds,
+            |x, y, z| value((z * resolution[1] + y) * resolution[0] + x).max(0.0),
+            scale,
+        ))
+    }
+
+    /// 密なボクセル値からグリッドを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `resolution` - 各軸のボクセル数
+    /// * `bounds` - グリッドが存在する範囲
+    /// * `value` - ボクセル位置(x, y, z)の値（0.0以上）
+    /// * `scale` - ボクセル値に掛ける倍率
+    pub fn from_dense(
+        resolution: [usize; 3],
+        bounds: AABB,
+        value: impl Fn(usize, usize, usize) -> f32,
+        scale: f64,
+    ) -> Self {
+        let bricks = resolution.map(|n| n.div_ceil(BRICK_SIZE));
+
+        let mut data = Vec::with_capacity(bricks[0] * bricks[1] * bricks[2]);
+        for bz in 0..bricks[2] {
+            for by in 0..bricks[1] {
+                for bx in 0..bricks[0] {
+                    let mut brick = vec![0.0_f32; BRICK_SIZE * BRICK_SIZE * BRICK_SIZE];
+                    let mut empty = true;
+                    for (i, voxel) in brick.iter_mut().enumerate() {
+                        let x = bx * BRICK_SIZE + i % BRICK_SIZE;
+                        let y = by * BRICK_SIZE + (i / BRICK_SIZE) % BRICK_SIZE;
+                        let z = bz * BRICK_SIZE + i / (BRICK_SIZE * BRICK_SIZE);
+                        if x < resolution[0] && y < resolution[1] && z < resolution[2] {
+                            *voxel = value(x, y, z);
+                            empty &= *voxel == 0.0;
+                        }
+                    }
+                    data.push((!empty).then(|| brick.into_boxed_slice()));
+                }
+            }
+        }
+
+        let mut grid = VoxelGrid {
+            resolution,
+            bounds,
+            bricks,
+            data,
+            majorants: Vec::new(),
+            scale,
+        };
+        grid.majorants = grid.build_majorants();
+        grid
+    }
+
+    /// ブリックごとの優関数を計算
+    ///
+    /// ブリックの範囲の補間には次のブリックの先頭のボクセルも使うため、それも含める
+    fn build_majorants(&self) -> Vec<f64> {
+        let mut majorants = Vec::with_capacity(self.data.len());
+        for bz in 0..self.bricks[2] {
+            for by in 0..self.bricks[1] {
+                for bx in 0..self.bricks[0] {
+                    let range = |b: usize, axis: usize| {
+                        b * BRICK_SIZE..((b + 1) * BRICK_SIZE + 1).min(self.resolution[axis])
+                    };
+                    let mut max = 0.0_f32;
+                    for z in range(bz, 2) {
+                        for y in range(by, 1) {
+                            for x in range(bx, 0) {
+                                max = max.max(self.voxel(x, y, z));
+                            }
+                        }
+                    }
+                    majorants.push(max as f64 * self.scale);
+                }
+            }
+        }
+        majorants
+    }
+
+    /// ボクセル位置(x, y, z)の値（倍率を掛ける前）
+    fn voxel(&self, x: usize, y: usize, z: usize) -> f32 {
+        let brick =
+            ((z / BRICK_SIZE) * self.bricks[1] + y / BRICK_SIZE) * self.bricks[0] + x / BRICK_SIZE;
+        match &self.data[brick] {
+            Some(values) => {
+                let local =
+                    ((z % BRICK_SIZE) * BRICK_SIZE + y % BRICK_SIZE) * BRICK_SIZE + x % BRICK_SIZE;
+                values[local]
+            }
+            None => 0.0,
+        }
+    }
+
+    /// ワールド座標の点をボクセル単位の座標に変換
+    fn to_voxel_space(&self, point: Vec3Glam) -> [f64; 3] {
+        let extent = self.bounds.max() - self.bounds.min();
+        let local = point - self.bounds.min();
+        std::array::from_fn(|axis| {
+            let cells = (self.resolution[axis] - 1) as f64;
+            if extent.get(axis) > 0.0 {
+                local.get(axis) / extent.get(axis) * cells
+            } else {
+                0.0
+            }
+        })
+    }
+}
+
+impl DensityField for VoxelGrid {
+    /// 周囲8個のボクセルを三線形補間した値（範囲外は0）
+    fn density(&self, point: Vec3Glam) -> f64 {
+        let p = self.to_voxel_space(point);
+        let mut base = [0_usize; 3];
+        let mut frac = [0.0_f64; 3];
+        for axis in 0..3 {
+            let max = (self.resolution[axis] - 1) as f64;
+            if !(0.0..=max).contains(&p[axis]) {
+                return 0.0;
+            }
+            // 最後のボクセルの位置では1つ手前の区間で補間する
+            let i = (p[axis].floor() as usize).min(self.resolution[axis].saturating_sub(2));
+            base[axis] = i;
+            frac[axis] = p[axis] - i as f64;
+        }
+
+        let mut value = 0.0;
+        for corner in 0..8 {
+            let offset = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+            let mut weight = 1.0;
+            let mut index = [0_usize; 3];
+            for axis in 0..3 {
+                let f = frac[axis];
+                weight *= if offset[axis] == 1 { f } else { 1.0 - f };
+                index[axis] = (base[axis] + offset[axis]).min(self.resolution[axis] - 1);
+            }
+            if weight > 0.0 {
+                value += weight * self.voxel(index[0], index[1], index[2]) as f64;
+            }
+        }
+        value * self.scale
+    }
+
+    fn max_density(&self) -> f64 {
+        self.majorants.iter().copied().fold(0.0, f64::max)
+    }
+
+    fn bounds(&self) -> Option<AABB> {
+        Some(self.bounds)
+    }
+
+    /// 3D-DDAでレイが通るブリックを順にたどり、ブリックごとの優関数で区間を分割
+    fn majorants(&self, ray: &Ray, t0: f64, t1: f64) -> Vec<MajorantSegment> {
+        let mut segments = Vec::new();
+        let Some((t0, t1)) = self.bounds.interval(ray, t0, t1) else {
+            return segments;
+        };
+
+        // ブリック単位の座標でのレイの原点と方向
+        let origin = self
+            .to_voxel_space(ray.origin())
+            .map(|v| v / BRICK_SIZE as f64);
+        let end = self
+            .to_voxel_space(ray.at(1.0))
+            .map(|v| v / BRICK_SIZE as f64);
+        let direction: [f64; 3] = std::array::from_fn(|axis| end[axis] - origin[axis]);
+
+        let mut cell = [0_usize; 3];
+        let mut next = [f64::INFINITY; 3];
+        let mut delta = [f64::INFINITY; 3];
+        for axis in 0..3 {
+            let p = origin[axis] + direction[axis] * t0;
+            cell[axis] = (p.floor().max(0.0) as usize).min(self.bricks[axis] - 1);
+            if direction[axis] > 0.0 {
+                next[axis] = t0 + ((cell[axis] + 1) as f64 - p) / direction[axis];
+                delta[axis] = 1.0 / direction[axis];
+            } else if direction[axis] < 0.0 {
+                next[axis] = t0 + (cell[axis] as f64 - p) / direction[axis];
+                delta[axis] = -1.0 / direction[axis];
+            }
+        }
+
+        let mut t = t0;
+        while t < t1 {
+            let axis = if next[0] < next[1] && next[0] < next[2] {
+                0
+            } else if next[1] < next[2] {
+                1
+            } else {
+                2
+            };
+            let exit = next[axis].min(t1);
+            let brick = (cell[2] * self.bricks[1] + cell[1]) * self.bricks[0] + cell[0];
+            let density = self.majorants[brick];
+
+            // 同じ優関数の区間はまとめ、空のブリックは飛ばす
+            match segments.last_mut() {
+                Some(MajorantSegment {
+                    t1: last_end,
+                    density: last,
+                    ..
+                }) if *last == density && *last_end == t => *last_end = exit,
+                _ if density > 0.0 => segments.push(MajorantSegment {
+                    t0: t,
+                    t1: exit,
+                    density,
+                }),
+                _ => {}
+            }
+
+            t = exit;
+            next[axis] += delta[axis];
+            let step_forward = delta[axis].is_finite() && direction[axis] > 0.0;
+            if step_forward {
+                cell[axis] += 1;
+                if cell[axis] >= self.bricks[axis] {
+                    break;
+                }
+            } else if cell[axis] == 0 {
+                break;
+            } else {
+                cell[axis] -= 1;
+            }
+        }
+        segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 指定したボクセル数とチャンネル数のuint8形式のヘッダーを作成
+    fn header(dims: [i32; 3], channels: i32) -> Vec<u8> {
+        let mut bytes = b"VOL".to_vec();
+        bytes.push(3);
+        for value in [3, dims[0], dims[1], dims[2], channels] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [0.0_f32, 0.0, 0.0, 1.0, 1.0, 1.0] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn parses_complete_grid() {
+        let mut bytes = header([2, 2, 2], 1);
+        bytes.extend_from_slice(&[255; 8]);
+        assert!(VoxelGrid::parse(&bytes, 1.0).is_ok());
+    }
+
+    #[test]
+    fn rejects_truncated_header() {
+        let bytes = header([2, 2, 2], 1);
+        assert!(VoxelGrid::parse(&bytes[..40], 1.0).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let mut bytes = header([2, 2, 2], 1);
+        bytes.extend_from_slice(&[255; 7]);
+        assert!(VoxelGrid::parse(&bytes, 1.0).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_header() {
+        let bytes = header([i32::MAX, i32::MAX, i32::MAX], i32::MAX);
+        assert!(VoxelGrid::parse(&bytes, 1.0).is_err());
+    }
+}
@@ -0,0 +1,352 @@
+use rand::Rng;
+use std::sync::Arc;
+
+use super::{Isotropic, Medium, MediumEvent, PhaseFunction, channel_probabilities};
+use crate::aabb::AABB;
+use crate::ray::Ray;
+use crate::texture::{Noise, Texture};
+use crate::vec3_glam::{ColorGlam, Vec3Glam};
+
+/// 黒体放射の色を求める波長（nm、RGBの順）
+const BLACKBODY_WAVELENGTHS: [f64; 3] = [610.0, 550.0, 465.0];
+
+/// 空間的に変化する媒質の密度
+pub trait DensityField: Send + Sync {
+    /// 指定位置の密度（0.0以上）
+    fn density(&self, point: Vec3Glam) -> f64;
+
+    /// 密度の上限（デルタトラッキングの優関数に使用）
+    fn max_density(&self) -> f64;
+
+    /// 密度が0でない範囲（Noneの場合は範囲を持たない）
+    fn bounds(&self) -> Option<AABB> {
+        None
+    }
+
+    /// レイのt0 ~ t1の区間を、密度の上限が一定の小区間に分割
+    ///
+    /// 上限が0の区間は含めなくてよい。デフォルトは区間全体をmax_densityとする
+    fn majorants(&self, _ray: &Ray, t0: f64, t1: f64) -> Vec<MajorantSegment> {
+        vec![MajorantSegment {
+            t0,
+            t1,
+            density: self.max_density(),
+        }]
+    }
+}
+
+/// 密度の上限が一定のレイの区間
+pub struct MajorantSegment {
+    /// 区間の始まりのレイのパラメータ
+    pub t0: f64,
+    /// 区間の終わりのレイのパラメータ
+    pub t1: f64,
+    /// 区間内の密度の上限
+    pub density: f64,
+}
+
+/// フラクタルノイズで密度が変化する雲や煙
+pub struct NoiseDensity {
+    noise: Noise,
+    /// 密度の最大値
+    density: f64,
+    /// ノイズの周波数
+    scale: f64,
+    octaves: u32,
+    /// 密度が0になるノイズ値のしきい値（0.0 ~ 1.0）
+    coverage: f64,
+}
+
+impl NoiseDensity {
+    /// ノイズによる密度場を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `noise` - ノイズ関数
+    /// * `density` - 密度の最大値
+    /// * `scale` - ノイズの周波数
+    /// * `octaves` - 重ねるオクターブ数
+    /// * `coverage` - 密度が0になるノイズ値のしきい値（大きいほど隙間が増える）
+    pub fn new(noise: Noise, density: f64, scale: f64, octaves: u32, coverage: f64) -> Self {
+        NoiseDensity {
+            noise,
+            density,
+            scale,
+            octaves,
+            coverage: coverage.clamp(0.0, 0.99),
+        }
+    }
+}
+
+impl DensityField for NoiseDensity {
+    fn density(&self, point: Vec3Glam) -> f64 {
+        let value = 0.5 * (self.noise.fbm(point * self.scale, self.octaves) + 1.0);
+        self.density * ((value - self.coverage) / (1.0 - self.coverage)).clamp(0.0, 1.0)
+    }
+
+    fn max_density(&self) -> f64 {
+        self.density
+    }
+}
+
+/// テクスチャの輝度に比例する密度
+///
+/// テクスチャは位置のみで評価するため、ノイズや3Dチェッカーなどの立体的なテクスチャに向く
+pub struct TextureDensity {
+    texture: Arc<dyn Texture>,
+    /// 輝度1での密度
+    density: f64,
+}
+
+impl TextureDensity {
+    /// テクスチャによる密度場を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - 密度を決めるテクスチャ（輝度は0.0 ~ 1.0に制限される）
+    /// * `density` - 輝度1での密度
+    pub fn new(texture: Arc<dyn Texture>, density: f64) -> Self {
+        TextureDensity { texture, density }
+    }
+}
+
+impl DensityField for TextureDensity {
+    fn density(&self, point: Vec3Glam) -> f64 {
+        self.density
+            * self
+                .texture
+                .value(0.0, 0.0, point)
+                .luminance()
+                .clamp(0.0, 1.0)
+    }
+
+    fn max_density(&self) -> f64 {
+        self.density
+    }
+}
+
+/// 密度が空間的に変化する散乱媒質
+///
+/// 係数は密度1あたりの値で、実際の係数は位置の密度を掛けたものになる。
+/// 密度場が区間ごとに与える優関数（majorant）によるデルタトラッキングで自由行程を、
+/// レシオトラッキングで透過率を求める
+pub struct HeterogeneousMedium {
+    density: Arc<dyn DensityField>,
+    /// 密度1あたりの吸収係数
+    absorption: ColorGlam,
+    /// 密度1あたりの散乱係数
+    scattering: ColorGlam,
+    /// 媒質が存在する範囲
+    bounds: AABB,
+    /// 散乱の方向分布
+    phase: Arc<dyn PhaseFunction>,
+    /// 黒体放射の温度場（ケルビン）と放射の強さ
+    emission: Option<(Arc<dyn DensityField>, f64)>,
+}
+
+impl HeterogeneousMedium {
+    /// 密度場と係数から媒質を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `density` - 密度場
+    /// * `absorption` - 密度1あたりの吸収係数（RGBごと）
+    /// * `scattering` - 密度1あたりの散乱係数（RGBごと）
+    /// * `bounds` - 媒質が存在する範囲（外側の密度は0として扱う）
+    pub fn new(
+        density: Arc<dyn DensityField>,
+        absorption: ColorGlam,
+        scattering: ColorGlam,
+        bounds: AABB,
+    ) -> Self {
+        HeterogeneousMedium {
+            density,
+            absorption,
+            scattering,
+            bounds,
+            phase: Arc::new(Isotropic),
+            emission: None,
+        }
+    }
+
+    /// 位相関数を設定（デフォルトは等方散乱）
+    pub fn with_phase(mut self, phase: Arc<dyn PhaseFunction>) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    /// 温度に応じた黒体放射で発光させる（炎や爆発）
+    ///
+    /// 単位長さあたりの放射は、密度 x 強さ x 温度の黒体の分光放射輝度（W/(sr·m²·nm)）
+    ///
+    /// # Arguments
+    ///
+    /// * `temperature` - 温度場（ケルビン）
+    /// * `intensity` - 放射の強さの倍率
+    pub fn with_emission(mut self, temperature: Arc<dyn DensityField>, intensity: f64) -> Self {
+        self.emission = Some((temperature, intensity));
+        self
+    }
+
+    /// 全チャンネルのうち最大の、密度1あたりの減衰係数
+    fn max_extinction(&self) -> f64 {
+        let extinction = self.absorption + self.scattering;
+        extinction.x().max(extinction.y()).max(extinction.z())
+    }
+
+    /// レイの0 ~ t_maxのうち媒質の範囲内を通る区間を、優関数が一定の小区間に分割
+    fn segments(&self, ray: &Ray, t_max: f64) -> Vec<MajorantSegment> {
+        match self.bounds.interval(ray, 0.0, t_max) {
+            Some((t0, t1)) => self.density.majorants(ray, t0, t1),
+            None => Vec::new(),
+        }
+    }
+
+    /// レシオトラッキングで区間内の仮の衝突点をたどる
+    ///
+    /// 各衝突点で、そこまでの透過率の推定値とレイのパラメータ、単位パラメータあたりの
+    /// 優関数をvisitに渡す。戻り値はt_maxまでの透過率の推定値
+    fn ratio_track(
+        &self,
+        ray: &Ray,
+        t_max: f64,
+        mut visit: impl FnMut(ColorGlam, f64, f64),
+    ) -> ColorGlam {
+        let mut transmittance = ColorGlam::new(1.0, 1.0, 1.0);
+        let speed = ray.direction().length();
+        let extinction = self.absorption + self.scattering;
+        let max_extinction = self.max_extinction();
+        let mut rng = rand::thread_rng();
+
+        for segment in self.segments(ray, t_max) {
+            let majorant = max_extinction * segment.density * speed;
+            if majorant <= 0.0 {
+                continue;
+            }
+            let mut t = segment.t0;
+            loop {
+                t -= (1.0 - rng.gen_range(0.0..1.0_f64)).ln() / majorant;
+                if t >= segment.t1 {
+                    break;
+                }
+
+                visit(transmittance, t, majorant);
+                let density = self.density.density(ray.at(t)) * speed;
+                let ratio = |i: usize| (1.0 - extinction.get(i) * density / majorant).max(0.0);
+                transmittance = transmittance * ColorGlam::new(ratio(0), ratio(1), ratio(2));
+
+                // 透過率が小さくなったらロシアンルーレットで打ち切る
+                let max = transmittance
+                    .x()
+                    .max(transmittance.y())
+                    .max(transmittance.z());
+                if max < 0.1 {
+                    let survival = max.max(0.05);
+                    if rng.gen_range(0.0..1.0) >= survival {
+                        return ColorGlam::new(0.0, 0.0, 0.0);
+                    }
+                    transmittance = transmittance / survival;
+                }
+            }
+        }
+        transmittance
+    }
+}
+
+/// 温度の黒体の分光放射輝度（W/(sr·m²·nm)、RGBの代表波長で評価）
+fn blackbody(kelvin: f64) -> ColorGlam {
+    if kelvin <= 0.0 {
+        return ColorGlam::new(0.0, 0.0, 0.0);
+    }
+    // 2hc² (W·m²/sr) と hc/k (m·K)
+    const C1: f64 = 1.191_042_97e-16;
+    const C2: f64 = 1.438_776_9e-2;
+    let radiance = |i: usize| {
+        let lambda = BLACKBODY_WAVELENGTHS[i] * 1e-9;
+        C1 / (lambda.powi(5) * (C2 / (lambda * kelvin)).exp_m1()) * 1e-9
+    };
+    ColorGlam::new(radiance(0), radiance(1), radiance(2))
+}
+
+impl Medium for HeterogeneousMedium {
+    /// デルタトラッキングで次の散乱までの距離をサンプリング
+    ///
+    /// 優関数に従って仮の衝突点を選び、散乱か空衝突（null collision）かを確率的に決める。
+    /// 色ごとに異なる係数は、散乱の確率をスループットで重み付けした平均で決め、
+    /// 吸収と色ごとの差は重みで補正する
+    fn sample(&self, ray: &Ray, t_max: f64, throughput: ColorGlam) -> MediumEvent {
+        let one = ColorGlam::new(1.0, 1.0, 1.0);
+        let speed = ray.direction().length();
+        let max_extinction = self.max_extinction();
+        let probabilities = channel_probabilities(throughput);
+        let mut rng = rand::thread_rng();
+        let mut weight = one;
+
+        // 優関数が変わる区間の境界では、指数分布の無記憶性により距離を引き直してよい
+        for segment in self.segments(ray, t_max) {
+            let majorant = max_extinction * segment.density * speed;
+            if majorant <= 0.0 {
+                continue;
+            }
+            let mut t = segment.t0;
+            loop {
+                t -= (1.0 - rng.gen_range(0.0..1.0_f64)).ln() / majorant;
+                if t >= segment.t1 {
+                    break;
+                }
+
+                let density = self.density.density(ray.at(t)) * speed;
+                let scattering = self.scattering * density;
+                let p_scatter = (probabilities.dot(&scattering) / majorant).min(1.0);
+                if rng.gen_range(0.0..1.0) < p_scatter {
+                    return MediumEvent::Scatter {
+                        t,
+                        weight: weight * scattering / (majorant * p_scatter),
+                    };
+                }
+
+                // 空衝突では吸収された割合を重みから差し引いて進む。
+                // 散乱の確率が1の場合は空衝突の確率が0で重みが定まらないため、パスを打ち切る
+                let p_null = 1.0 - p_scatter;
+                if p_null <= 0.0 {
+                    return MediumEvent::Pass {
+                        weight: ColorGlam::new(0.0, 0.0, 0.0),
+                    };
+                }
+                let null = one * majorant - (self.absorption + self.scattering) * density;
+                weight = weight * null / (majorant * p_null);
+                if weight.x().max(weight.y()).max(weight.z()) <= 0.0 {
+                    return MediumEvent::Pass { weight };
+                }
+            }
+        }
+        MediumEvent::Pass { weight }
+    }
+
+    /// レシオトラッキングで透過率を推定
+    fn transmittance(&self, ray: &Ray, t_max: f64) -> ColorGlam {
+        self.ratio_track(ray, t_max, |_, _, _| {})
+    }
+
+    /// 仮の衝突点ごとに、そこまでの透過率で減衰させた放射を加えて推定
+    fn emission(&self, ray: &Ray, t_max: f64) -> ColorGlam {
+        let Some((temperature, intensity)) = &self.emission else {
+            return ColorGlam::new(0.0, 0.0, 0.0);
+        };
+        let speed = ray.direction().length();
+        let mut emission = ColorGlam::new(0.0, 0.0, 0.0);
+        self.ratio_track(ray, t_max, |transmittance, t, majorant| {
+            let point = ray.at(t);
+            let density = self.density.density(point) * speed;
+            if density > 0.0 {
+                let radiance = blackbody(temperature.density(point)) * *intensity;
+                emission = emission + transmittance * radiance * (density / majorant);
+            }
+        });
+        emission
+    }
+
+    fn phase(&self) -> &dyn PhaseFunction {
+        self.phase.as_ref()
+    }
+}
@@ -0,0 +1,136 @@
+use rand::Rng;
+use std::sync::Arc;
+
+use super::{Isotropic, Medium, MediumEvent, PhaseFunction, channel_probabilities};
+use crate::ray::Ray;
+use crate::vec3_glam::ColorGlam;
+
+/// 係数が一様な散乱媒質（表面下散乱、一様な密度のボリューム用）
+///
+/// 係数は単位長さあたりの値で、RGBごとに異なってよい
+#[derive(Debug)]
+pub struct HomogeneousMedium {
+    /// 吸収係数
+    absorption: ColorGlam,
+    /// 散乱係数
+    scattering: ColorGlam,
+    /// 散乱の方向分布
+    phase: Arc<dyn PhaseFunction>,
+    /// 不透明な表面に囲まれているか
+    enclosed: bool,
+}
+
+impl HomogeneousMedium {
+    /// 吸収係数と散乱係数から媒質を作成
+    pub fn new(absorption: ColorGlam, scattering: ColorGlam) -> Self {
+        HomogeneousMedium {
+            absorption,
+            scattering,
+            phase: Arc::new(Isotropic),
+            enclosed: false,
+        }
+    }
+
+    /// 単一散乱アルベドと平均自由行程から媒質を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `albedo` - 散乱係数と減衰係数の比（RGBごと、0.0 ~ 1.0）
+    /// * `mean_free_path` - 次の相互作用までの平均距離（RGBごと）
+    pub fn from_albedo(albedo: ColorGlam, mean_free_path: ColorGlam) -> Self {
+        let extinction = |i: usize| 1.0 / mean_free_path.get(i).max(1e-6);
+        let extinction = ColorGlam::new(extinction(0), extinction(1), extinction(2));
+        let scattering = albedo * extinction;
+        HomogeneousMedium {
+            absorption: extinction - scattering,
+            scattering,
+            phase: Arc::new(Isotropic),
+            enclosed: false,
+        }
+    }
+
+    /// 位相関数を設定（デフォルトは等方散乱）
+    pub fn with_phase(mut self, phase: Arc<dyn PhaseFunction>) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    /// 不透明な表面に囲まれた媒質とする（内部の散乱点で直接光を評価しない）
+    pub fn with_enclosed(mut self, enclosed: bool) -> Self {
+        self.enclosed = enclosed;
+        self
+    }
+
+    fn extinction(&self) -> ColorGlam {
+        self.absorption + self.scattering
+    }
+}
+
+impl Medium for HomogeneousMedium {
+    /// 次の散乱までの距離をサンプリング
+    ///
+    /// RGBのうち1チャンネルの減衰係数で距離を選び、各チャンネルの確率密度の混合で重み付けする。
+    /// チャンネルはパスのスループットに比例した確率で選び、色ごとの重みの偏りを抑える
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - 媒質内を進むレイ
+    /// * `t_max` - 媒質の境界（次の衝突点）のレイのパラメータ
+    /// * `throughput` - ここまでのパスのスループット
+    fn sample(&self, ray: &Ray, t_max: f64, throughput: ColorGlam) -> MediumEvent {
+        let mut rng = rand::thread_rng();
+        let speed = ray.direction().length();
+        let extinction = self.extinction();
+
+        let probabilities = channel_probabilities(throughput);
+        let u = rng.gen_range(0.0..1.0);
+        let channel = if u < probabilities.x() {
+            0
+        } else if u < probabilities.x() + probabilities.y() {
+            1
+        } else {
+            2
+        };
+        let sigma = extinction.get(channel);
+        let distance = if sigma > 0.0 {
+            -(1.0 - rng.gen_range(0.0..1.0_f64)).ln() / sigma
+        } else {
+            f64::INFINITY
+        };
+
+        let transmittance = |d: f64| {
+            let tr = |i: usize| (-extinction.get(i) * d).exp();
+            ColorGlam::new(tr(0), tr(1), tr(2))
+        };
+
+        let max_distance = t_max * speed;
+        if distance < max_distance {
+            let tr = transmittance(distance);
+            let pdf = probabilities.dot(&(extinction * tr));
+            MediumEvent::Scatter {
+                t: distance / speed,
+                weight: self.scattering * tr / pdf,
+            }
+        } else {
+            let tr = transmittance(max_distance);
+            MediumEvent::Pass {
+                weight: tr / probabilities.dot(&tr),
+            }
+        }
+    }
+
+    fn transmittance(&self, ray: &Ray, t_max: f64) -> ColorGlam {
+        let distance = t_max * ray.direction().length();
+        let extinction = self.extinction();
+        let tr = |i: usize| (-extinction.get(i) * distance).exp();
+        ColorGlam::new(tr(0), tr(1), tr(2))
+    }
+
+    fn phase(&self) -> &dyn PhaseFunction {
+        self.phase.as_ref()
+    }
+
+    fn is_enclosed(&self) -> bool {
+        self.enclosed
+    }
+}
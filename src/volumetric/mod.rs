@@ -2,6 +2,15 @@ use crate::ray::Ray;
 use crate::vec3_glam::ColorGlam;
 
 pub mod fog;
+pub mod grid;
+pub mod heterogeneous;
+pub mod homogeneous;
+pub mod phase;
+
+pub use grid::VoxelGrid;
+pub use heterogeneous::{DensityField, HeterogeneousMedium, NoiseDensity, TextureDensity};
+pub use homogeneous::HomogeneousMedium;
+pub use phase::{DoubleHenyeyGreenstein, HenyeyGreenstein, Isotropic, PhaseFunction};
 
 /// ボリューメトリック効果の基本特性を定義するトレイト
 pub trait VolumetricMedium: Send + Sync {
@@ -17,6 +26,76 @@ pub trait VolumetricMedium: Send + Sync {
     fn sample(&self, ray: &Ray, t_min: f64, t_max: f64) -> (ColorGlam, f64);
 }
 
+/// 媒質内で自由行程をサンプリングした結果
+pub enum MediumEvent {
+    /// 衝突点より手前で散乱した
+    Scatter {
+        /// 散乱点のレイのパラメータ
+        t: f64,
+        /// 散乱までの経路の重み（透過率・散乱係数 / 確率密度）
+        weight: ColorGlam,
+    },
+    /// 散乱せずに衝突点まで到達した
+    Pass {
+        /// 衝突点までの経路の重み（透過率 / 確率）
+        weight: ColorGlam,
+    },
+}
+
+/// 散乱によってパスが続く関与媒質（ボリュームのパストレーシング用）
+///
+/// レイは原点から媒質の内部を進むものとして扱う
+pub trait Medium: Send + Sync {
+    /// 次の散乱までの距離をサンプリング
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - 媒質内を進むレイ
+    /// * `t_max` - 次の衝突点のレイのパラメータ
+    /// * `throughput` - ここまでのパスのスループット（色ごとの重みの偏りを抑えるために使用）
+    fn sample(&self, ray: &Ray, t_max: f64, throughput: ColorGlam) -> MediumEvent;
+
+    /// レイの原点からt_maxまでの透過率（シャドウレイ用）
+    fn transmittance(&self, ray: &Ray, t_max: f64) -> ColorGlam;
+
+    /// 散乱の方向分布を表す位相関数
+    fn phase(&self) -> &dyn PhaseFunction;
+
+    /// レイの原点からt_maxまでに媒質が放射し、原点まで届く光（炎など）
+    fn emission(&self, _ray: &Ray, _t_max: f64) -> ColorGlam {
+        ColorGlam::new(0.0, 0.0, 0.0)
+    }
+
+    /// 不透明な表面に囲まれ、内部から光源が見えない媒質か（表面下散乱など）
+    ///
+    /// trueの場合、散乱点での直接光の評価を省く
+    fn is_enclosed(&self) -> bool {
+        false
+    }
+}
+
+impl std::fmt::Debug for dyn Medium {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Medium")
+    }
+}
+
+impl std::fmt::Debug for dyn PhaseFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PhaseFunction")
+    }
+}
+
+/// 色ごとに異なる係数を扱う際に各チャンネルを選ぶ確率（スループットに比例）
+fn channel_probabilities(throughput: ColorGlam) -> ColorGlam {
+    let total = throughput.x() + throughput.y() + throughput.z();
+    if total > 0.0 {
+        throughput / total
+    } else {
+        ColorGlam::new(1.0, 1.0, 1.0) / 3.0
+    }
+}
+
 /// Beer-Lambertの法則に基づく光の減衰を計算
 pub fn calculate_beer_lambert_attenuation(density: f64, distance: f64) -> f64 {
     (-density * distance).exp()
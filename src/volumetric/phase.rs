@@ -0,0 +1,131 @@
+use rand::Rng;
+use std::f64::consts::PI;
+
+use crate::types::{orthonormal_basis, random_unit_vector};
+use crate::vec3_glam::Vec3Glam;
+
+/// 媒質内の散乱で光が進む方向の分布
+///
+/// 方向は散乱点に入射したレイの進行方向と、散乱後の進行方向で表す
+pub trait PhaseFunction: Send + Sync {
+    /// 進行方向directionのレイが方向wiへ散乱する確率密度（立体角あたり）
+    fn evaluate(&self, direction: Vec3Glam, wi: Vec3Glam) -> f64;
+
+    /// 散乱後の方向をサンプリング（位相関数に比例する確率で選ぶ）
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - 散乱点に入射したレイの進行方向（単位ベクトル）
+    fn sample(&self, direction: Vec3Glam) -> Vec3Glam;
+}
+
+/// すべての方向へ等しく散乱する位相関数
+pub struct Isotropic;
+
+impl PhaseFunction for Isotropic {
+    fn evaluate(&self, _direction: Vec3Glam, _wi: Vec3Glam) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn sample(&self, _direction: Vec3Glam) -> Vec3Glam {
+        random_unit_vector()
+    }
+}
+
+/// Henyey–Greensteinの位相関数
+///
+/// 非対称パラメータgが正なら前方、負なら後方へ散乱しやすい（霞や雲は前方散乱が強い）
+pub struct HenyeyGreenstein {
+    /// 散乱角の余弦の平均（-1.0 ~ 1.0）
+    g: f64,
+}
+
+impl HenyeyGreenstein {
+    /// 非対称パラメータから位相関数を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `g` - 非対称パラメータ（0.0で等方散乱、1.0に近いほど前方に集中する）
+    pub fn new(g: f64) -> Self {
+        HenyeyGreenstein {
+            g: g.clamp(-0.99, 0.99),
+        }
+    }
+
+    /// 散乱角の余弦に対する確率密度
+    fn density(&self, cos_theta: f64) -> f64 {
+        let g = self.g;
+        let denom = 1.0 + g * g - 2.0 * g * cos_theta;
+        (1.0 - g * g) / (4.0 * PI * denom * denom.sqrt())
+    }
+
+    /// 累積分布の逆関数で散乱角の余弦をサンプリング
+    fn sample_cos_theta(&self, u: f64) -> f64 {
+        let g = self.g;
+        if g.abs() < 1e-3 {
+            return 1.0 - 2.0 * u;
+        }
+        let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * u);
+        ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+    }
+}
+
+impl PhaseFunction for HenyeyGreenstein {
+    fn evaluate(&self, direction: Vec3Glam, wi: Vec3Glam) -> f64 {
+        self.density(direction.unit_vector().dot(&wi.unit_vector()))
+    }
+
+    fn sample(&self, direction: Vec3Glam) -> Vec3Glam {
+        let mut rng = rand::thread_rng();
+        let cos_theta = self.sample_cos_theta(rng.gen_range(0.0..1.0));
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = rng.gen_range(0.0..2.0 * PI);
+
+        let w = direction.unit_vector();
+        let (u, v) = orthonormal_basis(w);
+        u * (sin_theta * phi.cos()) + v * (sin_theta * phi.sin()) + w * cos_theta
+    }
+}
+
+/// 前方と後方の2つのHenyey–Greensteinローブを重ねた位相関数
+///
+/// 単一のローブでは表せない、前方の強い散乱と弱い後方散乱を併せ持つ雲などに使う
+pub struct DoubleHenyeyGreenstein {
+    forward: HenyeyGreenstein,
+    backward: HenyeyGreenstein,
+    /// 前方ローブの重み（0.0 ~ 1.0）
+    weight: f64,
+}
+
+impl DoubleHenyeyGreenstein {
+    /// 2つのローブから位相関数を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `forward` - 前方ローブの非対称パラメータ
+    /// * `backward` - 後方ローブの非対称パラメータ
+    /// * `weight` - 前方ローブの重み（残りが後方ローブ）
+    pub fn new(forward: f64, backward: f64, weight: f64) -> Self {
+        DoubleHenyeyGreenstein {
+            forward: HenyeyGreenstein::new(forward),
+            backward: HenyeyGreenstein::new(backward),
+            weight: weight.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl PhaseFunction for DoubleHenyeyGreenstein {
+    fn evaluate(&self, direction: Vec3Glam, wi: Vec3Glam) -> f64 {
+        self.weight * self.forward.evaluate(direction, wi)
+            + (1.0 - self.weight) * self.backward.evaluate(direction, wi)
+    }
+
+    /// 重みに従ってローブを選んでサンプリング（確率密度は2つの混合と一致する）
+    fn sample(&self, direction: Vec3Glam) -> Vec3Glam {
+        if rand::thread_rng().gen_range(0.0..1.0) < self.weight {
+            self.forward.sample(direction)
+        } else {
+            self.backward.sample(direction)
+        }
+    }
+}